@@ -0,0 +1,550 @@
+use std::{
+    env, fs,
+    io::{self, BufReader},
+    process,
+    str::FromStr,
+    sync::OnceLock,
+    thread,
+    time::Instant,
+};
+
+use crate::profile::{bytes, Usage};
+use crate::runner::{Allocations, Report, Timings};
+
+pub struct Args {
+    args: Vec<String>,
+}
+
+impl Args {
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.args
+            .iter()
+            .position(|a| a == name)
+            .and_then(|i| self.args.get(i + 1))
+            .map(String::as_str)
+    }
+    pub fn flag(&self, name: &str) -> bool {
+        self.args.iter().any(|a| a == name)
+    }
+    pub fn parse<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.value(name).map(|v| {
+            v.parse()
+                .unwrap_or_else(|_| fail(&format!("Invalid value '{}' for {}", v, name)))
+        })
+    }
+}
+
+static ARGS: OnceLock<Args> = OnceLock::new();
+
+/// The command line, so days can pick up their own options like `--vocabulary`
+pub fn args() -> &'static Args {
+    ARGS.get_or_init(|| Args {
+        args: env::args().skip(1).collect(),
+    })
+}
+
+/// `--vocabulary FILE`, English by default
+pub fn vocabulary(args: &Args) -> Result<crate::day1::Vocabulary, String> {
+    match args.value("--vocabulary") {
+        Some(path) => crate::day1::Vocabulary::from_file(path),
+        None => Ok(crate::day1::Vocabulary::english()),
+    }
+}
+
+/// `--bag BAG` or the bag in `--bag-file FILE`, the puzzle's bag by default
+pub fn bag(args: &Args) -> Result<crate::day2::Draw, String> {
+    match (args.value("--bag"), args.value("--bag-file")) {
+        (Some(bag), _) => crate::day2::parse_bag(bag),
+        (None, Some(path)) => fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path, e))
+            .and_then(|bag| crate::day2::parse_bag(&bag)),
+        (None, None) => crate::day2::parse_bag(crate::day2::DEFAULT_BAG),
+    }
+}
+
+/// The puzzle's gear rule adjusted by `--gear-symbols`, `--gear-count` (or `--gear-min` and
+/// `--gear-max`) and `--gear-aggregate product|sum`
+pub fn gear_rule(args: &Args) -> Result<crate::day3::GearRule, String> {
+    use crate::day3::{Aggregate, GearRule};
+
+    let mut rule = GearRule::default();
+    if let Some(symbols) = args.value("--gear-symbols") {
+        rule.symbols = symbols.chars().collect();
+    }
+    if let Some(count) = args.parse("--gear-count") {
+        (rule.min, rule.max) = (count, count);
+    }
+    rule.min = args.parse("--gear-min").unwrap_or(rule.min);
+    rule.max = args.parse("--gear-max").unwrap_or(rule.max);
+    if rule.min > rule.max {
+        return Err(format!(
+            "--gear-min {} is above --gear-max {}",
+            rule.min, rule.max
+        ));
+    }
+    rule.aggregate = match args.value("--gear-aggregate") {
+        None | Some("product") => Aggregate::Product,
+        Some("sum") => Aggregate::Sum,
+        Some(a) => return Err(format!("Unknown gear aggregate '{}'", a)),
+    };
+    Ok(rule)
+}
+
+/// `--scoring doubling|linear|fibonacci|capped:N` and `--copies next|previous`, the puzzle's rules
+/// by default
+pub fn card_rules(args: &Args) -> Result<crate::day4::Rules, String> {
+    let mut rules = crate::day4::Rules::default();
+    if let Some(scoring) = args.value("--scoring") {
+        rules.scoring = scoring.parse()?;
+    }
+    if let Some(copies) = args.value("--copies") {
+        rules.copies = copies.parse()?;
+    }
+    Ok(rules)
+}
+
+pub fn fail(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    process::exit(1)
+}
+
+fn input(day: u32) -> String {
+    crate::runner::read_input(day).unwrap_or_else(|e| fail(&e))
+}
+
+const USAGE: &str = "usage:
+    run [--day N] [--watch] [--example]
+    run --day 1 --vocabulary FILE
+    run --day 2 [--bag BAG | --bag-file FILE]
+    calibrate --input FILE|- [--vocabulary FILE]
+    games minimal|stats|possible [--bag BAG | --bag-file FILE]
+    games admit --ids 1,2,3
+    games exactly --k N
+    schematic isolated
+    schematic at --row R --col C
+    gears [--json] [--gear-symbols CHARS] [--gear-count N | --gear-min N --gear-max N]
+          [--gear-aggregate product|sum]
+    run --day 4 [--scoring doubling|linear|fibonacci|capped:N] [--copies next|previous]
+    cascade [--json] [--copies next|previous]
+    bench-cards [--cards N] [--range R]
+    almanac validate
+    almanac check [--exhaustive N] [--samples N]
+    almanac compose [--inverse]
+    almanac seeds --location L
+    almanac map --from CATEGORY --to CATEGORY [--value N]
+    races
+    run --all [--jobs N]
+    submit --day N --part P [--endpoint URL]
+    new --day N";
+
+fn timings(t: &Timings) -> String {
+    format!(
+        "parse: {:.1?}, part1: {:.1?}, part2: {:.1?}",
+        t.parse, t.part1, t.part2
+    )
+}
+
+fn usage(u: &Usage) -> String {
+    format!("{} allocs / {} peak", u.count, bytes(u.peak))
+}
+
+fn allocations(a: &Allocations) -> String {
+    format!(
+        "parse: {}, part1: {}, part2: {}",
+        usage(&a.parse),
+        usage(&a.part1),
+        usage(&a.part2)
+    )
+}
+
+/// The answer, or `failed` when the part couldn't be solved
+fn answer(a: &Result<String, String>) -> &str {
+    a.as_deref().unwrap_or("failed")
+}
+
+fn summary(reports: &[Report]) {
+    println!(
+        "{:>3}  {:>16}  {:>16}  {:>10}  {:>10}  {:>10}",
+        "day", "part1", "part2", "parse", "time1", "time2"
+    );
+    for r in reports {
+        match &r.result {
+            Ok(s) => println!(
+                "{:>3}  {:>16}  {:>16}  {:>10.1?}  {:>10.1?}  {:>10.1?}",
+                r.day,
+                answer(&s.answers[0]),
+                answer(&s.answers[1]),
+                s.timings.parse,
+                s.timings.part1,
+                s.timings.part2
+            ),
+            Err(e) => println!("{:>3}  error: {}", r.day, e),
+        }
+    }
+    for r in reports {
+        let answers = r.result.iter().flat_map(|s| s.answers.iter());
+        for (part, e) in answers
+            .enumerate()
+            .filter_map(|(i, a)| Some((i + 1, a.as_ref().err()?)))
+        {
+            println!("{:>3}  part{} error: {}", r.day, part, e);
+        }
+    }
+    for r in reports {
+        if let Some(a) = r.result.as_ref().ok().and_then(|s| s.allocations.as_ref()) {
+            println!("{:>3}  {}", r.day, allocations(a));
+        }
+    }
+}
+
+fn run_all(args: &Args) {
+    let jobs = args
+        .parse("--jobs")
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    let start = Instant::now();
+    let reports = crate::runner::run_all(jobs.max(1));
+    summary(&reports);
+    println!("total: {:.1?} on {} jobs", start.elapsed(), jobs);
+}
+
+fn run(args: &Args) {
+    if args.flag("--all") {
+        return run_all(args);
+    }
+    let day = args
+        .parse("--day")
+        .unwrap_or_else(|| crate::runner::DAYS.last().unwrap().day);
+    if args.flag("--watch") {
+        crate::runner::find(day).unwrap_or_else(|e| fail(&e));
+        crate::watch::watch(day)
+    }
+    let solved = crate::runner::run(day).unwrap_or_else(|e| fail(&e));
+    let [ans1, ans2] = &solved.answers;
+    println!("part1: {}, part2: {}", answer(ans1), answer(ans2));
+    println!("{}", timings(&solved.timings));
+    if let Some(a) = &solved.allocations {
+        println!("{}", allocations(a));
+    }
+    let errors: Vec<String> = (1..)
+        .zip(solved.answers.iter())
+        .filter_map(|(part, a)| Some(format!("part{}: {}", part, a.as_ref().err()?)))
+        .collect();
+    if !errors.is_empty() {
+        fail(&errors.join("\n"));
+    }
+}
+
+fn submit(args: &Args) {
+    let day = args.parse("--day").unwrap_or_else(|| fail(USAGE));
+    let part: u32 = args.parse("--part").unwrap_or_else(|| fail(USAGE));
+    let answer = match part {
+        1 | 2 => crate::runner::run(day).unwrap_or_else(|e| fail(&e)).answers[part as usize - 1]
+            .clone()
+            .unwrap_or_else(|e| fail(&e)),
+        _ => fail("--part must be 1 or 2"),
+    };
+    println!("Submitting {} for day {} part {}", answer, day, part);
+    match crate::submit::submit(day, part, &answer, args.value("--endpoint")) {
+        Ok(outcome) => println!("{}", outcome),
+        Err(e) => fail(&e),
+    }
+}
+
+fn calibrate(args: &Args) {
+    let vocabulary = vocabulary(args).unwrap_or_else(|e| fail(&e));
+    let calibration = match args.value("--input") {
+        Some("-") => crate::day1::calibrate(io::stdin().lock(), &vocabulary),
+        Some(path) => fs::File::open(path)
+            .and_then(|f| crate::day1::calibrate(BufReader::new(f), &vocabulary)),
+        None => fail(USAGE),
+    }
+    .unwrap_or_else(|e| fail(&e.to_string()));
+    for e in calibration.errors.iter() {
+        eprintln!("{}", e);
+    }
+    println!("part1: {}, part2: {}", calibration.part1, calibration.part2);
+}
+
+fn games(args: &Args) {
+    use crate::day2::{Bag, Day2, Draw};
+    use crate::runner::Solution;
+
+    let bag = bag(args).unwrap_or_else(|e| fail(&e));
+    let (games, _) = Day2::parse(&input(2), &Bag(bag.clone())).unwrap_or_else(|e| fail(&e));
+    match args.args.get(1).map(String::as_str) {
+        Some("minimal") => {
+            for (index, draws) in games.iter() {
+                println!("Game {}: {}", index, crate::day2::minimal_bag(draws));
+            }
+        }
+        Some("stats") => {
+            println!(
+                "{:>8}  {:>6}  {:>6}  {:>6}  {:>6}",
+                "color", "games", "draws", "total", "max"
+            );
+            for (color, s) in crate::day2::color_stats(&games) {
+                println!(
+                    "{:>8}  {:>6}  {:>6}  {:>6}  {:>6}",
+                    color, s.games, s.draws, s.total, s.max
+                );
+            }
+        }
+        Some("possible") => {
+            let ids = crate::day2::possible_games(&games, &bag);
+            println!("{} games: {:?}", ids.len(), ids);
+        }
+        Some("admit") => {
+            let ids: Vec<u32> = args
+                .value("--ids")
+                .unwrap_or_else(|| fail(USAGE))
+                .split(',')
+                .map(|id| id.trim().parse().unwrap_or_else(|_| fail(USAGE)))
+                .collect();
+            match crate::day2::admitting_bag(&games, &ids) {
+                Ok(bag) => println!("Any bag with at least {} admits {:?}", bag, ids),
+                Err(e) => fail(&e),
+            }
+        }
+        Some("exactly") => {
+            let k = args.parse("--k").unwrap_or_else(|| fail(USAGE));
+            match crate::day2::tightest_uniform_bag(&games, k) {
+                Some(n) => {
+                    let bag: Draw = crate::day2::color_stats(&games)
+                        .keys()
+                        .map(|color| (color.as_str(), n))
+                        .collect();
+                    println!("{} makes exactly {} games possible", bag, k)
+                }
+                None => fail(&format!(
+                    "No uniform bag makes exactly {} games possible",
+                    k
+                )),
+            }
+        }
+        _ => fail(USAGE),
+    }
+}
+
+fn schematic(args: &Args) {
+    use crate::utils::Vec2;
+
+    let schematic = crate::day3::Schematic::parse(&input(3)).unwrap_or_else(|e| fail(&e));
+    match args.args.get(1).map(String::as_str) {
+        Some("isolated") => {
+            for n in schematic.isolated_numbers() {
+                println!("{} at row {}, cols {}..{}", n.value, n.row, n.start, n.end);
+            }
+        }
+        Some("at") => {
+            let pos = Vec2 {
+                x: args.parse("--col").unwrap_or_else(|| fail(USAGE)),
+                y: args.parse("--row").unwrap_or_else(|| fail(USAGE)),
+            };
+            if let Some(n) = schematic.number_at(pos) {
+                let symbols: Vec<char> = schematic
+                    .symbols_adjacent_to(n)
+                    .iter()
+                    .map(|s| s.c)
+                    .collect();
+                println!("number {} touches symbols {:?}", n.value, symbols);
+            } else if let Some(s) = schematic.symbol_at(pos) {
+                let numbers: Vec<u32> = schematic
+                    .numbers_adjacent_to(s)
+                    .iter()
+                    .map(|n| n.value)
+                    .collect();
+                println!("symbol {} touches numbers {:?}", s.c, numbers);
+            } else {
+                println!("nothing at row {}, col {}", pos.y, pos.x);
+            }
+        }
+        _ => fail(USAGE),
+    }
+}
+
+fn gears(args: &Args) {
+    let schematic = crate::day3::Schematic::parse(&input(3)).unwrap_or_else(|e| fail(&e));
+    let gears = schematic.gears(&gear_rule(args).unwrap_or_else(|e| fail(&e)));
+    if args.flag("--json") {
+        let gears: Vec<String> = gears.iter().map(|g| g.to_json()).collect();
+        println!("[{}]", gears.join(","));
+        return;
+    }
+    for g in gears.iter() {
+        let numbers: Vec<u32> = g.numbers.iter().map(|n| n.value).collect();
+        println!(
+            "{} at row {}, col {}: {:?} -> {}",
+            g.symbol.c, g.symbol.pos.y, g.symbol.pos.x, numbers, g.ratio
+        );
+    }
+    println!(
+        "{} gears, total ratio {}",
+        gears.len(),
+        gears.iter().map(|g| &g.ratio).sum::<num::BigUint>()
+    );
+}
+
+fn cascade(args: &Args) {
+    use crate::day4::Day4;
+    use crate::runner::Solution;
+
+    let rules = card_rules(args).unwrap_or_else(|e| fail(&e));
+    let (cards, rules) = Day4::parse(&input(4), &rules).unwrap_or_else(|e| fail(&e));
+    let pile = crate::day4::cascade(&cards, rules.copies);
+    if args.flag("--json") {
+        let pile: Vec<String> = pile.iter().map(|c| c.to_json()).collect();
+        println!("[{}]", pile.join(","));
+        return;
+    }
+    println!(
+        "{:>5}  {:>7}  {:>10}  won from",
+        "card", "matches", "copies"
+    );
+    for c in pile.iter() {
+        let won_from: Vec<String> = c
+            .won_from
+            .iter()
+            .map(|(card, copies)| format!("{}x{}", copies, card))
+            .collect();
+        println!(
+            "{:>5}  {:>7}  {:>10}  {}",
+            c.card,
+            c.matches,
+            c.copies,
+            won_from.join(", ")
+        );
+    }
+}
+
+fn bench_cards(args: &Args) {
+    let cards = args.parse("--cards").unwrap_or(1_000_000);
+    let range = args.parse("--range").unwrap_or(100);
+    if range == 0 {
+        fail("--range must be at least 1");
+    }
+    let (hashset, bitset) = crate::day4::bench(cards, range).unwrap_or_else(|e| fail(&e));
+    println!(
+        "{} cards from 1..={}: hashset {:.1?}, bitset {:.1?}",
+        cards, range, hashset, bitset
+    );
+}
+
+fn almanac(args: &Args) {
+    use crate::day5::Day5;
+    use crate::runner::Solution;
+
+    let input = input(5);
+    if args.args.get(1).map(String::as_str) == Some("validate") {
+        let mut problems: Vec<String> = crate::day5::validate(&input)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        // The graph of categories is only looked at once every line reads
+        if problems.is_empty() {
+            problems.extend(Day5::parse(&input, &()).err());
+        }
+        for p in problems.iter() {
+            println!("{}", p);
+        }
+        match problems.len() {
+            0 => println!("The almanac is valid"),
+            1 => fail("1 problem"),
+            n => fail(&format!("{} problems", n)),
+        }
+        return;
+    }
+    let almanac = Day5::parse(&input, &()).unwrap_or_else(|e| fail(&e));
+    let (seeds, mappings) = (
+        &almanac.seeds,
+        almanac
+            .chain("seed", "location")
+            .unwrap_or_else(|e| fail(&e)),
+    );
+    match args.args.get(1).map(String::as_str) {
+        Some("check") => {
+            let exhaustive = args.parse("--exhaustive").unwrap_or(1_000_000);
+            let samples = args.parse("--samples").unwrap_or(1000);
+            match crate::day5::check(seeds, &mappings, exhaustive, samples) {
+                Ok(n) => println!("{} seeds map into the mapped ranges", n),
+                Err(e) => fail(&e),
+            }
+        }
+        Some("compose") if args.flag("--inverse") => {
+            match crate::day5::compose(&mappings).inverse() {
+                Some(inverse) => print!("{}", inverse),
+                None => fail("Some locations are reached from several seeds, or from none"),
+            }
+        }
+        Some("compose") => print!("{}", crate::day5::compose(&mappings)),
+        Some("map") => {
+            let from = args.value("--from").unwrap_or_else(|| fail(USAGE));
+            let to = args.value("--to").unwrap_or_else(|| fail(USAGE));
+            let chain = almanac.chain(from, to).unwrap_or_else(|e| fail(&e));
+            let map = crate::day5::compose(&chain);
+            match args.parse("--value") {
+                Some(value) => println!("{} {} is {} {}", from, value, to, map.get(value)),
+                None => print!("{}", map),
+            }
+        }
+        Some("seeds") => {
+            let location = args.parse("--location").unwrap_or_else(|| fail(USAGE));
+            let planted = crate::day5::seed_ranges(seeds).unwrap_or_else(|e| fail(&e));
+            for seed in crate::day5::seeds_for(&mappings, location) {
+                match planted.iter().find(|r| r.contains(&seed)) {
+                    Some(r) => println!("{} (planted in {}..{})", seed, r.start, r.end),
+                    None => println!("{}", seed),
+                }
+            }
+        }
+        _ => fail(USAGE),
+    }
+}
+
+fn races(args: &Args) {
+    use crate::day6::Day6;
+    use crate::runner::Solution;
+
+    match args.args.get(1).map(String::as_str) {
+        None => {
+            let (races, race) = Day6::parse(&input(6), &()).unwrap_or_else(|e| fail(&e));
+            for r in races.iter().chain([&race]) {
+                let winning = r.winning();
+                println!(
+                    "time {}, record {}: hold {}..{} ({} ways)",
+                    r.time,
+                    r.distance,
+                    winning.start,
+                    winning.end,
+                    &winning.end - &winning.start
+                );
+            }
+        }
+        _ => fail(USAGE),
+    }
+}
+
+fn new(args: &Args) {
+    let day = args.parse("--day").unwrap_or_else(|| fail(USAGE));
+    match crate::scaffold::new_day(day) {
+        Ok(()) => println!("Created day{}", day),
+        Err(e) => fail(&e),
+    }
+}
+
+pub fn main() {
+    let args = args();
+    match args.args.first().map(String::as_str) {
+        None | Some("run") => run(args),
+        Some("submit") => submit(args),
+        Some("new") => new(args),
+        Some("calibrate") => calibrate(args),
+        Some("games") => games(args),
+        Some("schematic") => schematic(args),
+        Some("gears") => gears(args),
+        Some("cascade") => cascade(args),
+        Some("bench-cards") => bench_cards(args),
+        Some("almanac") => almanac(args),
+        Some("races") => races(args),
+        _ => fail(USAGE),
+    }
+}
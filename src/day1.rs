@@ -1,54 +1,372 @@
 use nom::{
-    branch::alt, bytes::complete::tag, bytes::complete::take, character::complete::alpha0,
-    character::complete::one_of, combinator::map, error::Error, multi::many1, sequence::preceded,
-    sequence::terminated, IResult,
+    branch::alt,
+    bytes::complete::{tag, take_till1},
+    character::complete::{one_of, space1, u32},
+    combinator::{map, value},
+    sequence::{preceded, separated_pair},
+    IResult,
+};
+use std::{
+    fmt, fs,
+    io::{self, BufRead},
 };
 
 const DIGITS: &str = "0123456789";
+const ENGLISH: &str = include_str!("vocabularies/english.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Occurrence {
+    pub offset: usize,
+    pub digit: u32,
+    pub spelled: bool,
+}
+
+/// Whether a spelled digit may share letters with the next one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Matching {
+    /// Every offset is tried, so `oneight` is 1 and 8
+    Overlapping,
+    /// Scanning resumes after the longest word, so the `i` ending `viii` isn't read again as 1
+    NonOverlapping,
+}
 
-fn part1(input: &str) -> IResult<&str, Vec<u32>> {
-    preceded(
-        alpha0::<&str, Error<&str>>,
-        many1(terminated(
-            map(one_of(DIGITS), |s: char| s.to_digit(10).unwrap()),
-            alpha0,
-        )),
-    )(input.trim())
+/// Spelled out digits, one `word value` pair per line. A `matching non-overlapping` line switches
+/// from the default overlapping matches
+#[derive(Debug, Clone)]
+pub struct Vocabulary {
+    words: Vec<(String, u32)>,
+    matching: Matching,
 }
 
-fn number_parser(input: &str) -> IResult<&str, u32> {
+#[derive(Debug, Clone, PartialEq)]
+enum Entry {
+    Word(String, u32),
+    Matching(Matching),
+}
+
+fn entry_parser(input: &str) -> IResult<&str, Entry> {
     alt((
-        map(tag("zero"), |_| 0),
-        map(tag("one"), |_| 1),
-        map(tag("two"), |_| 2),
-        map(tag("three"), |_| 3),
-        map(tag("four"), |_| 4),
-        map(tag("five"), |_| 5),
-        map(tag("six"), |_| 6),
-        map(tag("seven"), |_| 7),
-        map(tag("eight"), |_| 8),
-        map(tag("nine"), |_| 9),
-        map(one_of(DIGITS), |s: char| s.to_digit(10).unwrap()),
-        preceded(take(1usize), number_parser),
+        map(
+            preceded(
+                tag("matching "),
+                alt((
+                    value(Matching::Overlapping, tag("overlapping")),
+                    value(Matching::NonOverlapping, tag("non-overlapping")),
+                )),
+            ),
+            Entry::Matching,
+        ),
+        map(
+            separated_pair(take_till1(|c: char| c.is_whitespace()), space1, u32),
+            |(word, value): (&str, u32)| Entry::Word(word.into(), value),
+        ),
     ))(input)
 }
 
-fn part2(input: &str) -> IResult<&str, Vec<u32>> {
-    many1(number_parser)(input.trim())
+impl Default for Vocabulary {
+    fn default() -> Self {
+        Vocabulary::english()
+    }
+}
+
+impl Vocabulary {
+    pub fn english() -> Vocabulary {
+        Vocabulary::parse(ENGLISH).unwrap()
+    }
+    pub fn parse(input: &str) -> Result<Vocabulary, String> {
+        let mut vocabulary = Vocabulary {
+            words: vec![],
+            matching: Matching::Overlapping,
+        };
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim();
+            match entry_parser(line) {
+                _ if line.is_empty() => {}
+                Ok(("", Entry::Word(word, value))) if value <= 9 => {
+                    vocabulary.words.push((word, value))
+                }
+                Ok(("", Entry::Word(word, value))) => {
+                    return Err(format!(
+                        "line {}: '{}' spells {}, which is not a digit",
+                        i + 1,
+                        word,
+                        value
+                    ))
+                }
+                Ok(("", Entry::Matching(matching))) => vocabulary.matching = matching,
+                _ => {
+                    return Err(format!(
+                        "line {}: invalid vocabulary entry '{}'",
+                        i + 1,
+                        line
+                    ))
+                }
+            }
+        }
+        match vocabulary.words.is_empty() {
+            true => Err("The vocabulary has no words".into()),
+            false => Ok(vocabulary),
+        }
+    }
+    pub fn from_file(path: &str) -> Result<Vocabulary, String> {
+        let input =
+            fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+        Vocabulary::parse(&input).map_err(|e| format!("{}: {}", path, e))
+    }
+    /// The value and length of the longest word starting `input`, so `iii` is 3 rather than 1 in
+    /// roman numerals
+    fn spelled(&self, input: &str) -> Option<(u32, usize)> {
+        self.words
+            .iter()
+            .filter(|(word, _)| input.starts_with(word.as_str()))
+            .max_by_key(|(word, _)| word.len())
+            .map(|(word, value)| (*value, word.len()))
+    }
+}
+
+fn digit_parser(input: &str) -> IResult<&str, u32> {
+    map(one_of(DIGITS), |s: char| s.to_digit(10).unwrap())(input)
+}
+
+/// Every digit in `line` with its byte offset. With overlapping matching words are matched at
+/// each offset without consuming anything, so overlaps like `oneight` yield both 1 and 8
+pub fn scan<'a>(
+    line: &'a str,
+    vocabulary: &'a Vocabulary,
+) -> impl Iterator<Item = Occurrence> + 'a {
+    let mut resume = 0;
+    line.char_indices().filter_map(move |(offset, _)| {
+        if offset < resume {
+            return None;
+        }
+        let rest = &line[offset..];
+        match (digit_parser(rest), vocabulary.spelled(rest)) {
+            (Ok((_, digit)), _) => Some(Occurrence {
+                offset,
+                digit,
+                spelled: false,
+            }),
+            (_, Some((digit, len))) => {
+                if vocabulary.matching == Matching::NonOverlapping {
+                    resume = offset + len;
+                }
+                Some(Occurrence {
+                    offset,
+                    digit,
+                    spelled: true,
+                })
+            }
+            _ => None,
+        }
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Calibrated {
+    first: Occurrence,
+    last: Occurrence,
+}
+
+impl Calibrated {
+    fn value(&self) -> u64 {
+        (self.first.digit * 10 + self.last.digit) as u64
+    }
+}
+
+/// First and last digit of a line, ignoring spelled digits (part 1) and including them (part 2)
+fn calibrate_line(line: &str, vocabulary: &Vocabulary) -> [Option<Calibrated>; 2] {
+    scan(line, vocabulary).fold([None, None], |[numeric, spelled], o| {
+        let add = |c: Option<Calibrated>| match c {
+            Some(c) => Calibrated { last: o, ..c },
+            None => Calibrated { first: o, last: o },
+        };
+        match o.spelled {
+            true => [numeric, Some(add(spelled))],
+            false => [Some(add(numeric)), Some(add(spelled))],
+        }
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MissingDigit {
+    pub line: usize,
+    pub part: u8,
+}
+
+impl fmt::Display for MissingDigit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} has no digit for part {}", self.line, self.part)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Calibration {
+    pub part1: u64,
+    pub part2: u64,
+    pub errors: Vec<MissingDigit>,
 }
 
-pub fn main() {
-    let mut ans1 = 0;
-    let mut ans2 = 0;
-    if let Ok(lines) = crate::utils::read_lines("src/inputs/day1.txt") {
-        for line in lines {
-            if let Ok(chars) = line {
-                let (_remaining, nums) = part1(&chars).unwrap();
-                ans1 += nums.first().unwrap() * 10 + nums.last().unwrap();
-                let (_remaining, nums) = part2(&chars).unwrap();
-                ans2 += nums.first().unwrap() * 10 + nums.last().unwrap();
+/// Sums both parts in a single pass over `reader`, reusing one line buffer so the input never has
+/// to fit in memory. Lines without a digit are skipped and reported in `errors`
+pub fn calibrate<R: BufRead>(mut reader: R, vocabulary: &Vocabulary) -> io::Result<Calibration> {
+    let mut calibration = Calibration::default();
+    let mut buf = String::new();
+    let mut line = 0;
+    while reader.read_line(&mut buf)? > 0 {
+        line += 1;
+        let [part1, part2] = calibrate_line(buf.trim_end(), vocabulary);
+        for (part, (c, sum)) in [
+            (part1, &mut calibration.part1),
+            (part2, &mut calibration.part2),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            match c {
+                Some(c) => *sum += c.value(),
+                None => calibration.errors.push(MissingDigit {
+                    line,
+                    part: part as u8 + 1,
+                }),
             }
         }
+        buf.clear();
+    }
+    Ok(calibration)
+}
+
+/// The puzzle lines with the vocabulary to read them in, each part scans them on its own so their
+/// timings are comparable. Lines without a digit count as 0, `calibrate` reports them
+pub struct Document<'a> {
+    vocabulary: Vocabulary,
+    lines: Vec<&'a str>,
+}
+
+impl Document<'_> {
+    fn total(&self, part: usize) -> u64 {
+        self.lines
+            .iter()
+            .filter_map(|line| calibrate_line(line, &self.vocabulary)[part])
+            .map(|c| c.value())
+            .sum()
+    }
+}
+
+pub struct Day1;
+
+impl crate::runner::Solution for Day1 {
+    type Options = Vocabulary;
+    type Parsed<'a> = Document<'a>;
+
+    fn options(args: &crate::cli::Args) -> Result<Self::Options, String> {
+        crate::cli::vocabulary(args)
+    }
+
+    fn parse<'a>(input: &'a str, vocabulary: &Self::Options) -> Result<Self::Parsed<'a>, String> {
+        Ok(Document {
+            vocabulary: vocabulary.clone(),
+            lines: input.lines().collect(),
+        })
+    }
+
+    fn part1(document: &Self::Parsed<'_>) -> Result<String, String> {
+        Ok(document.total(0).to_string())
+    }
+
+    fn part2(document: &Self::Parsed<'_>) -> Result<String, String> {
+        Ok(document.total(1).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(line: &str) -> Vec<(u32, bool)> {
+        scan(line, &Vocabulary::english())
+            .map(|o| (o.digit, o.spelled))
+            .collect()
+    }
+
+    fn values(line: &str) -> [Option<u64>; 2] {
+        calibrate_line(line, &Vocabulary::english()).map(|c| c.map(|c| c.value()))
+    }
+
+    #[test]
+    fn overlapping_words() {
+        assert_eq!(digits("oneight"), [(1, true), (8, true)]);
+        assert_eq!(digits("twone"), [(2, true), (1, true)]);
+        assert_eq!(digits("eightwothree"), [(8, true), (2, true), (3, true)]);
+        assert_eq!(values("oneight"), [None, Some(18)]);
+        assert_eq!(values("twone"), [None, Some(21)]);
+    }
+
+    #[test]
+    fn offsets() {
+        let offsets: Vec<usize> = scan("a1twone", &Vocabulary::english())
+            .map(|o| o.offset)
+            .collect();
+        assert_eq!(offsets, [1, 2, 4]);
+    }
+
+    #[test]
+    fn only_numeric() {
+        assert_eq!(digits("a1b2c3"), [(1, false), (2, false), (3, false)]);
+        assert_eq!(values("a1b2c3"), [Some(13), Some(13)]);
+        assert_eq!(values("treb7uchet"), [Some(77), Some(77)]);
+    }
+
+    #[test]
+    fn only_spelled() {
+        assert_eq!(values("abcone2threexyz"), [Some(22), Some(13)]);
+        assert_eq!(values("sevenine"), [None, Some(79)]);
+        assert_eq!(values("xyz"), [None, None]);
+    }
+
+    #[test]
+    fn missing_digits() {
+        let calibration =
+            calibrate("1abc2\nseven\nxyz\n".as_bytes(), &Vocabulary::english()).unwrap();
+        assert_eq!((calibration.part1, calibration.part2), (12, 12 + 77));
+        assert_eq!(
+            calibration.errors,
+            [
+                MissingDigit { line: 2, part: 1 },
+                MissingDigit { line: 3, part: 1 },
+                MissingDigit { line: 3, part: 2 }
+            ]
+        );
+    }
+
+    #[test]
+    fn roman_numerals() {
+        let roman = Vocabulary::parse(include_str!("vocabularies/roman.txt")).unwrap();
+        let values = |line| calibrate_line(line, &roman).map(|c| c.map(|c| c.value()));
+        assert_eq!(values("viii"), [None, Some(88)]);
+        assert_eq!(values("xvi"), [None, Some(66)]);
+        assert_eq!(values("ivii"), [None, Some(42)]);
+    }
+
+    #[test]
+    fn vocabulary_files() {
+        let v = Vocabulary::parse("one 1\n\n  two 2  \n").unwrap();
+        assert_eq!(v.words, [("one".into(), 1), ("two".into(), 2)]);
+        assert_eq!(v.matching, Matching::Overlapping);
+        assert!(Vocabulary::parse("ten 10").is_err());
+        assert!(Vocabulary::parse("one").is_err());
+        assert!(Vocabulary::parse("matching sometimes\none 1").is_err());
+        assert!(Vocabulary::parse("\n\n").is_err());
+    }
+
+    #[test]
+    fn parts_match_calibrate() {
+        use crate::runner::Solution;
+
+        let input = "1abc2\npqr3stu8vwx\nseven\nxtwone3four\n";
+        let vocabulary = Vocabulary::english();
+        let calibration = calibrate(input.as_bytes(), &vocabulary).unwrap();
+        let document = Day1::parse(input, &vocabulary).unwrap();
+        assert_eq!(Day1::part1(&document), Ok(calibration.part1.to_string()));
+        assert_eq!(Day1::part2(&document), Ok(calibration.part2.to_string()));
     }
-    println!("part1: {}, part2: {}", ans1, ans2);
 }
@@ -5,7 +5,7 @@ use std::{collections::HashMap, fmt};
 use crate::utils::Positioned;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Dir {
+pub enum Dir {
     North,
     East,
     South,
@@ -13,10 +13,10 @@ enum Dir {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Pipe(Dir, Dir);
+pub struct Pipe(Dir, Dir);
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
-enum Tile {
+pub enum Tile {
     Pipe(Pipe),
     Start(Option<Pipe>),
     Ground,
@@ -129,47 +129,41 @@ impl fmt::Debug for AnnotatedTile {
     }
 }
 
-fn parse() -> crate::utils::Map<Tile> {
+fn parse(input: &str) -> crate::utils::Map<Tile> {
     let mut area = crate::utils::Map {
         size: crate::utils::Vec2 { x: 0, y: 0 },
         elements: vec![],
     };
-    if let Ok(lines) = crate::utils::read_lines("src/inputs/day10.txt") {
-        for line in lines {
-            if let Ok(chars) = line {
-                let points = parse_line(&chars).unwrap().1;
-                area.size.y += 1;
-                area.size.x = points.len() as i64;
-                area.elements.extend(points);
-            }
-        }
+    for chars in input.lines() {
+        let points = parse_line(chars).unwrap().1;
+        area.size.y += 1;
+        area.size.x = points.len() as i64;
+        area.elements.extend(points);
     }
     area
 }
 
-fn parse_line<'a>(input: &'a str) -> IResult<&'a str, Vec<Tile>> {
+fn parse_line(input: &str) -> IResult<&str, Vec<Tile>> {
     map(not_line_ending, |res: &str| {
         res.chars().map(|c| c.into()).collect::<Vec<Tile>>()
     })(input)
 }
 
-pub fn main() {
-    let mut area = parse();
-    let mut route = vec![set_start_point(&mut area)];
-
-    let mut next_dir = match route[0].1 {
+fn find_loop(area: &crate::utils::Map<Tile>, start: Positioned<Tile>) -> Vec<Positioned<Tile>> {
+    let mut next_dir = match start.1 {
         Tile::Start(Some(p)) => p.0,
         _ => panic!("Set the pipe in the start value to continue!"),
     };
+    let mut route = vec![start];
 
     loop {
         let cur_element = &route[route.len() - 1];
-        let mut next_pos = cur_element.0.clone();
+        let mut next_pos = cur_element.0;
         match next_dir {
-            Dir::North => next_pos.y = next_pos.y - 1,
-            Dir::East => next_pos.x = next_pos.x + 1,
-            Dir::South => next_pos.y = next_pos.y + 1,
-            Dir::West => next_pos.x = next_pos.x - 1,
+            Dir::North => next_pos.y -= 1,
+            Dir::East => next_pos.x += 1,
+            Dir::South => next_pos.y += 1,
+            Dir::West => next_pos.x -= 1,
         };
         match area.get(next_pos).unwrap() {
             Tile::Start(Some(_)) => break,
@@ -180,8 +174,13 @@ pub fn main() {
             v => panic!("Could not complete loop '{:?}'", v),
         }
     }
+    route
+}
 
-    // dbg!(&route);
+fn annotate(
+    area: &crate::utils::Map<Tile>,
+    route: &[Positioned<Tile>],
+) -> crate::utils::Map<AnnotatedTile> {
     let mut annotated_area = crate::utils::Map {
         size: area.size,
         elements: area
@@ -191,7 +190,7 @@ pub fn main() {
             .collect(),
     };
 
-    for tile_with_pos in &route {
+    for tile_with_pos in route {
         let i = tile_with_pos.0.y * area.size.x + tile_with_pos.0.x;
         annotated_area.elements[i as usize].part_of_loop = true;
     }
@@ -216,23 +215,40 @@ pub fn main() {
         }
         e.inside = inside;
     }
-    dbg!(&annotated_area);
+    annotated_area
+}
 
-    let ans1 = route.len() / 2;
-    let ans2 = annotated_area
-        .elements
-        .iter()
-        .filter(|p| p.inside && !p.part_of_loop)
-        .count();
+pub struct Day10;
+
+impl crate::runner::Solution for Day10 {
+    type Options = ();
+    type Parsed<'a> = (crate::utils::Map<Tile>, Vec<Positioned<Tile>>);
 
-    println!("part1: {}, part2: {}", ans1, ans2);
+    fn parse<'a>(input: &'a str, _: &Self::Options) -> Result<Self::Parsed<'a>, String> {
+        let mut area = parse(input);
+        let start = set_start_point(&mut area);
+        let route = find_loop(&area, start);
+        Ok((area, route))
+    }
+
+    fn part1((_, route): &Self::Parsed<'_>) -> Result<String, String> {
+        Ok((route.len() / 2).to_string())
+    }
+
+    fn part2((area, route): &Self::Parsed<'_>) -> Result<String, String> {
+        Ok(annotate(area, route)
+            .elements
+            .iter()
+            .filter(|p| p.inside && !p.part_of_loop)
+            .count()
+            .to_string())
+    }
 }
 
 fn set_start_point(area: &mut crate::utils::Map<Tile>) -> Positioned<Tile> {
     let mut start = area
         .iter()
-        .filter(|p| p.1 == Tile::Start(None))
-        .next()
+        .find(|p| p.1 == Tile::Start(None))
         .expect("Map must contain a start point!");
 
     let mut first_dir: Option<Dir> = None;
@@ -242,7 +258,7 @@ fn set_start_point(area: &mut crate::utils::Map<Tile>) -> Positioned<Tile> {
         pos.y += diff.1;
         if let Some(pipe) = area.get(pos) {
             let valid_dir = match pipe {
-                Tile::Pipe(p) => p.has(&dir).then(|| dir.one80()),
+                Tile::Pipe(p) => p.has(dir).then(|| dir.one80()),
                 _ => None,
             };
             match (valid_dir, first_dir) {
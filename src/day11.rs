@@ -5,7 +5,7 @@ use std::{fmt, ops::Range};
 use crate::utils::{Map, Vec2};
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
-enum Space {
+pub enum Space {
     Empty,
     Galaxy,
     Distance,
@@ -35,25 +35,21 @@ impl fmt::Debug for Space {
     }
 }
 
-fn parse() -> Map<Space> {
+fn parse(input: &str) -> Map<Space> {
     let mut area = Map {
         size: crate::utils::Vec2 { x: 0, y: 0 },
         elements: vec![],
     };
-    if let Ok(lines) = crate::utils::read_lines("src/inputs/day11.txt") {
-        for line in lines {
-            if let Ok(chars) = line {
-                let points = parse_line(&chars).unwrap().1;
-                area.size.y += 1;
-                area.size.x = points.len() as i64;
-                area.elements.extend(points);
-            }
-        }
+    for chars in input.lines() {
+        let points = parse_line(chars).unwrap().1;
+        area.size.y += 1;
+        area.size.x = points.len() as i64;
+        area.elements.extend(points);
     }
     area
 }
 
-fn parse_line<'a>(input: &'a str) -> IResult<&'a str, Vec<Space>> {
+fn parse_line(input: &str) -> IResult<&str, Vec<Space>> {
     map(not_line_ending, |res: &str| {
         res.chars().map(|c| c.into()).collect::<Vec<Space>>()
     })(input)
@@ -78,12 +74,12 @@ fn find_empty_cols(space: &Map<Space>) -> Vec<i64> {
 }
 
 fn expand_space(space: &mut Map<Space>) {
-    for y in find_empty_rows(&space) {
+    for y in find_empty_rows(space) {
         let new_row_pos = ((space.size.x * y) as usize)..((space.size.x * (y + 1)) as usize);
         let new_row = repeat_n(Space::Distance, space.size.x as usize);
         space.elements.splice(new_row_pos, new_row);
     }
-    for x in find_empty_cols(&space).iter().rev() {
+    for x in find_empty_cols(space).iter().rev() {
         for y in 0..space.size.y {
             space.elements[(y * space.size.x + x) as usize] = Space::Distance;
         }
@@ -117,7 +113,7 @@ fn find_path(space: &Map<Space>, (g1, g2): &(Vec2, Vec2)) -> Vec<Space> {
     path
 }
 
-fn path_length(path: &Vec<Space>, distance: u128) -> u128 {
+fn path_length(path: &[Space], distance: u128) -> u128 {
     path.iter().fold(0, |acc, s| {
         acc + match s {
             Space::Distance => distance,
@@ -126,16 +122,30 @@ fn path_length(path: &Vec<Space>, distance: u128) -> u128 {
     })
 }
 
-pub fn main() {
-    let mut space = parse();
-    expand_space(&mut space);
-    let paths = galaxy_pairs(&space)
+fn total_distance(space: &Map<Space>, distance: u128) -> u128 {
+    galaxy_pairs(space)
         .iter()
-        .map(|p| find_path(&space, p))
-        .collect_vec();
+        .map(|p| path_length(&find_path(space, p), distance))
+        .sum()
+}
 
-    let ans1 = paths.iter().fold(0, |acc, p| acc + path_length(p, 2));
-    let ans2 = paths.iter().fold(0, |acc, p| acc + path_length(p, 1000000));
+pub struct Day11;
 
-    println!("part1: {}, part2: {}", ans1, ans2);
+impl crate::runner::Solution for Day11 {
+    type Options = ();
+    type Parsed<'a> = Map<Space>;
+
+    fn parse<'a>(input: &'a str, _: &Self::Options) -> Result<Self::Parsed<'a>, String> {
+        let mut space = parse(input);
+        expand_space(&mut space);
+        Ok(space)
+    }
+
+    fn part1(space: &Self::Parsed<'_>) -> Result<String, String> {
+        Ok(total_distance(space, 2).to_string())
+    }
+
+    fn part2(space: &Self::Parsed<'_>) -> Result<String, String> {
+        Ok(total_distance(space, 1000000).to_string())
+    }
 }
@@ -1,98 +1,353 @@
-use nom::branch::alt;
+use nom::character::complete::alpha1;
 use nom::combinator::{map, opt};
-use nom::sequence::{delimited, pair, terminated, tuple};
+use nom::sequence::{delimited, separated_pair, terminated, tuple};
 use nom::{bytes::complete::tag, character::complete::u32, multi::many1, IResult};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 
-#[derive(Debug, Clone, Copy)]
+pub const DEFAULT_BAG: &str = "12 red, 13 green, 14 blue";
+
+/// Cubes per colour, colours that were not drawn count as 0
+#[derive(Debug, Clone, Default)]
 pub struct Draw {
-    pub r: u32,
-    pub g: u32,
-    pub b: u32,
+    pub cubes: BTreeMap<String, u32>,
 }
 
+pub type Game = (u32, Vec<Draw>);
+
 impl PartialEq for Draw {
     fn eq(&self, other: &Self) -> bool {
-        self.r == other.r && self.g == other.g && self.b == other.b
+        self.colors(other).all(|c| self.get(c) == other.get(c))
     }
 }
 
+/// The product order: a draw is smaller when no colour has more cubes, draws with more of one
+/// colour but less of another are incomparable
 impl PartialOrd for Draw {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        if self.r <= other.r && self.g <= other.g && self.b <= other.b {
-            Some(std::cmp::Ordering::Less)
-        } else if self == other {
-            Some(std::cmp::Ordering::Equal)
-        } else {
-            None
+        match (self.dominates(other), other.dominates(self)) {
+            (true, true) => Some(std::cmp::Ordering::Equal),
+            (true, false) => Some(std::cmp::Ordering::Greater),
+            (false, true) => Some(std::cmp::Ordering::Less),
+            (false, false) => None,
         }
     }
 }
 
 impl Draw {
-    fn max(self, other: Draw) -> Draw {
-        Draw {
-            r: std::cmp::max(self.r, other.r),
-            g: std::cmp::max(self.g, other.g),
-            b: std::cmp::max(self.b, other.b),
+    pub fn get(&self, color: &str) -> u32 {
+        self.cubes.get(color).copied().unwrap_or(0)
+    }
+    /// Holds at least as many cubes of every colour as `other`
+    pub fn dominates(&self, other: &Draw) -> bool {
+        self.colors(other).all(|c| self.get(c) >= other.get(c))
+    }
+    fn colors<'a>(&'a self, other: &'a Draw) -> impl Iterator<Item = &'a str> {
+        self.cubes
+            .keys()
+            .chain(other.cubes.keys())
+            .map(String::as_str)
+    }
+    fn max(mut self, other: &Draw) -> Draw {
+        for (color, &value) in other.cubes.iter() {
+            let v = self.cubes.entry(color.clone()).or_insert(0);
+            *v = std::cmp::max(*v, value);
         }
+        self
+    }
+    /// Product of the cubes of every colour in the draw or in `bag`, a bag colour that was never
+    /// drawn makes it 0
+    fn power(&self, bag: &Draw) -> u32 {
+        let colors: BTreeSet<&str> = self.colors(bag).collect();
+        colors.into_iter().map(|c| self.get(c)).product()
     }
-    fn power(self) -> u32 {
-        self.r * self.g * self.b
+}
+
+impl fmt::Display for Draw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cubes: Vec<String> = self
+            .cubes
+            .iter()
+            .map(|(color, value)| format!("{} {}", value, color))
+            .collect();
+        write!(f, "{}", cubes.join(", "))
     }
 }
 
 impl<'a> FromIterator<(&'a str, u32)> for Draw {
     fn from_iter<I: IntoIterator<Item = (&'a str, u32)>>(iter: I) -> Self {
-        let mut draw = Draw { r: 0, g: 0, b: 0 };
-        for (color, value) in iter {
-            match color {
-                "r" => draw.r = value,
-                "g" => draw.g = value,
-                "b" => draw.b = value,
-                _ => {}
-            }
+        Draw {
+            cubes: iter
+                .into_iter()
+                .map(|(color, value)| (color.into(), value))
+                .collect(),
         }
-        draw
     }
 }
 
 fn draw_parser(input: &str) -> IResult<&str, Draw> {
-    let (remains, res) = many1(alt((
-        terminated(map(u32, |i| ("r", i)), pair(tag(" red"), opt(tag(", ")))),
-        terminated(map(u32, |i| ("g", i)), pair(tag(" green"), opt(tag(", ")))),
-        terminated(map(u32, |i| ("b", i)), pair(tag(" blue"), opt(tag(", ")))),
-    )))(input)?;
+    let (remains, res) = many1(terminated(
+        map(separated_pair(u32, tag(" "), alpha1), |(i, c)| (c, i)),
+        opt(tag(", ")),
+    ))(input)?;
     Ok((remains, res.into_iter().collect()))
 }
 
-fn game_parser(input: &str) -> IResult<&str, (u32, Vec<Draw>)> {
+fn game_parser(input: &str) -> IResult<&str, Game> {
     tuple((
         delimited(tag("Game "), u32, tag(": ")),
         many1(terminated(draw_parser, opt(tag("; ")))),
     ))(input.trim())
 }
 
-pub fn main() {
-    let max_draw = Draw {
-        r: 12,
-        g: 13,
-        b: 14,
+/// A bag like `12 red, 13 green, 14 blue`
+pub fn parse_bag(input: &str) -> Result<Draw, String> {
+    match draw_parser(input.trim()) {
+        Ok(("", bag)) => Ok(bag),
+        _ => Err(format!("Invalid bag '{}'", input.trim())),
+    }
+}
+
+/// The bag the elf loaded, `12 red, 13 green, 14 blue` unless given on the command line
+#[derive(Debug, Clone)]
+pub struct Bag(pub Draw);
+
+impl Default for Bag {
+    fn default() -> Self {
+        Bag(parse_bag(DEFAULT_BAG).unwrap())
+    }
+}
+
+/// The fewest cubes of each colour that make the game possible
+pub fn minimal_bag(draws: &[Draw]) -> Draw {
+    draws.iter().fold(Draw::default(), |acc, d| acc.max(d))
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ColorStats {
+    pub games: usize,
+    pub draws: usize,
+    pub total: u64,
+    pub max: u32,
+}
+
+pub fn color_stats(games: &[Game]) -> BTreeMap<String, ColorStats> {
+    let mut stats: BTreeMap<String, ColorStats> = BTreeMap::new();
+    for (_, draws) in games {
+        for (color, &value) in minimal_bag(draws).cubes.iter() {
+            let s = stats.entry(color.clone()).or_default();
+            s.games += 1;
+            s.max = s.max.max(value);
+        }
+        for (color, &value) in draws.iter().flat_map(|d| d.cubes.iter()) {
+            let s = stats.entry(color.clone()).or_default();
+            s.draws += 1;
+            s.total += value as u64;
+        }
+    }
+    stats
+}
+
+pub fn possible_games(games: &[Game], bag: &Draw) -> Vec<u32> {
+    games
+        .iter()
+        .filter(|(_, draws)| draws.iter().all(|d| bag.dominates(d)))
+        .map(|(index, _)| *index)
+        .collect()
+}
+
+/// The smallest bag admitting all of `ids`, every bag holding at least this many cubes admits them
+pub fn admitting_bag(games: &[Game], ids: &[u32]) -> Result<Draw, String> {
+    ids.iter().try_fold(Draw::default(), |acc, id| {
+        match games.iter().find(|(index, _)| index == id) {
+            Some((_, draws)) => Ok(acc.max(&minimal_bag(draws))),
+            None => Err(format!("There is no game {}", id)),
+        }
+    })
+}
+
+/// The smallest `n` such that a bag with `n` cubes of every colour makes exactly `k` games possible
+pub fn tightest_uniform_bag(games: &[Game], k: usize) -> Option<u32> {
+    let mut needed: Vec<u32> = games
+        .iter()
+        .map(|(_, draws)| minimal_bag(draws).cubes.into_values().max().unwrap_or(0))
+        .collect();
+    needed.sort();
+    let n = match k {
+        0 => 0,
+        k => *needed.get(k - 1)?,
     };
-    let mut ans1 = 0;
-    let mut ans2 = 0;
-    if let Ok(lines) = crate::utils::read_lines("src/inputs/day2.txt") {
-        for line in lines {
-            if let Ok(chars) = line {
-                let (_remaining, (index, draws)) = game_parser(&chars).unwrap();
-                if draws.iter().all(|d| *d <= max_draw) {
-                    ans1 += index;
-                }
-                ans2 += draws
+    match needed.get(k) {
+        Some(&next) if next <= n => None,
+        _ => Some(n),
+    }
+}
+
+pub struct Day2;
+
+impl crate::runner::Solution for Day2 {
+    type Options = Bag;
+    type Parsed<'a> = (Vec<Game>, Draw);
+
+    fn options(args: &crate::cli::Args) -> Result<Self::Options, String> {
+        crate::cli::bag(args).map(Bag)
+    }
+
+    fn parse<'a>(input: &'a str, Bag(bag): &Self::Options) -> Result<Self::Parsed<'a>, String> {
+        let games = input
+            .lines()
+            .map(|chars| game_parser(chars).unwrap().1)
+            .collect();
+        Ok((games, bag.clone()))
+    }
+
+    fn part1((games, max_draw): &Self::Parsed<'_>) -> Result<String, String> {
+        let ans: u32 = games
+            .iter()
+            .filter(|(_, draws)| draws.iter().all(|d| max_draw.dominates(d)))
+            .map(|(index, _)| index)
+            .sum();
+        Ok(ans.to_string())
+    }
+
+    fn part2((games, bag): &Self::Parsed<'_>) -> Result<String, String> {
+        let ans: u32 = games
+            .iter()
+            .map(|(_, draws)| minimal_bag(draws).power(bag))
+            .sum();
+        Ok(ans.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(s: &str) -> Draw {
+        parse_bag(s).unwrap()
+    }
+
+    #[test]
+    fn power_counts_missing_bag_colors() {
+        let Bag(bag) = Bag::default();
+        assert_eq!(draw("4 red, 2 green, 6 blue").power(&bag), 48);
+        assert_eq!(draw("4 red, 2 green").power(&bag), 0);
+        assert_eq!(draw("4 red, 2 green, 3 purple").power(&draw("1 red")), 24);
+    }
+
+    /// Every draw of up to 2 red and 2 green cubes, with each colour either drawn or left out
+    fn draws() -> Vec<Draw> {
+        let counts = [None, Some(0), Some(1), Some(2)];
+        counts
+            .iter()
+            .flat_map(|&red| counts.iter().map(move |&green| (red, green)))
+            .map(|(red, green)| {
+                [("red", red), ("green", green)]
                     .into_iter()
-                    .fold(Draw { r: 0, g: 0, b: 0 }, |acc, d| acc.max(d))
-                    .power();
+                    .filter_map(|(color, n)| Some((color, n?)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn partial_order_laws() {
+        use std::cmp::Ordering;
+
+        let draws = draws();
+        for a in draws.iter() {
+            assert!(a.le(a) && a.ge(a));
+            assert_eq!(a.partial_cmp(a), Some(Ordering::Equal));
+            for b in draws.iter() {
+                assert_eq!(a >= b, a.dominates(b), "{} >= {}", a, b);
+                assert_eq!(a.partial_cmp(b) == Some(Ordering::Equal), a == b);
+                assert_eq!(a.lt(b), a.le(b) && a.ne(b));
+                assert_eq!(a.partial_cmp(b), b.partial_cmp(a).map(Ordering::reverse));
+                if a <= b && b <= a {
+                    assert_eq!(a, b);
+                }
+                for c in draws.iter() {
+                    if a <= b && b <= c {
+                        assert!(a <= c, "{} <= {} <= {}", a, b, c);
+                    }
+                    if a < b && b < c {
+                        assert!(a < c, "{} < {} < {}", a, b, c);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn incomparable_draws() {
+        assert_eq!(draw("1 red").partial_cmp(&draw("1 green")), None);
+        assert_eq!(draw("0 red"), Draw::default());
+        assert!(draw("1 red, 1 green") > draw("1 red"));
+    }
+
+    fn games() -> Vec<Game> {
+        [
+            "Game 1: 2 red, 1 blue; 1 green",
+            "Game 2: 3 green; 1 red",
+            "Game 3: 3 blue, 1 red",
+            "Game 5: 7 red",
+        ]
+        .iter()
+        .map(|line| game_parser(line).unwrap().1)
+        .collect()
+    }
+
+    #[test]
+    fn stats() {
+        let stats = color_stats(&games());
+        let summary: Vec<(&str, usize, usize, u64, u32)> = stats
+            .iter()
+            .map(|(color, s)| (color.as_str(), s.games, s.draws, s.total, s.max))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("blue", 2, 2, 4, 3),
+                ("green", 2, 2, 4, 3),
+                ("red", 4, 4, 11, 7)
+            ]
+        );
+        let games = vec![(1, vec![draw("4294967295 red"), draw("4294967295 red")])];
+        let red = color_stats(&games)["red"];
+        assert_eq!((red.total, red.max), (2 * u32::MAX as u64, u32::MAX));
+    }
+
+    #[test]
+    fn admitting_bags() {
+        let games = games();
+        let bag = admitting_bag(&games, &[1, 3]).unwrap();
+        assert_eq!(bag, draw("2 red, 1 green, 3 blue"));
+        assert_eq!(possible_games(&games, &bag), [1, 3]);
+        assert_eq!(admitting_bag(&games, &[]), Ok(Draw::default()));
+        assert_eq!(
+            admitting_bag(&games, &[1, 4]),
+            Err("There is no game 4".into())
+        );
+    }
+
+    #[test]
+    fn tightest_uniform_bags() {
+        // The games need 2, 3, 3 and 7 cubes of their most drawn colour
+        let games = games();
+        let bags: Vec<Option<u32>> = (0..=5).map(|k| tightest_uniform_bag(&games, k)).collect();
+        // No bag lets in one of the games needing 3 without the other, and there are only 4 games
+        assert_eq!(bags, [Some(0), Some(2), None, Some(3), Some(7), None]);
+        for (k, n) in bags.iter().enumerate() {
+            if let Some(n) = n {
+                let bag = draw(&format!("{} red, {} green, {} blue", n, n, n));
+                assert_eq!(possible_games(&games, &bag).len(), k);
             }
         }
+        // A game without cubes is possible with the empty bag, so no bag admits none
+        let mut games = games;
+        games.push((9, vec![draw("0 red")]));
+        assert_eq!(tightest_uniform_bag(&games, 0), None);
+        assert_eq!(tightest_uniform_bag(&games, 1), Some(0));
+        assert_eq!(tightest_uniform_bag(&[], 0), Some(0));
     }
-    println!("{}, {}", ans1, ans2);
 }
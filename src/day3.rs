@@ -1,94 +1,356 @@
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take},
-    character::complete::digit1,
+    bytes::complete::tag,
+    character::complete::{anychar, digit1},
     combinator::{map, value},
-    multi::many1,
-    AsChar, IResult,
+    IResult,
 };
-use std::{collections::HashSet, fmt, str};
+use num::BigUint;
+use std::collections::HashMap;
 
-#[derive(Clone, Copy, PartialEq)]
-enum Point {
-    Number { i: usize, c: char, value: u32 },
+use crate::utils::Vec2;
+
+/// A part number spanning columns `start..end` of `row`. `id` is its index in
+/// `Schematic::numbers`, so equal values elsewhere in the grid are still different numbers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Number {
+    pub id: usize,
+    pub row: i64,
+    pub start: i64,
+    pub end: i64,
+    pub value: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Symbol {
+    pub pos: Vec2,
+    pub c: char,
+}
+
+impl Number {
+    fn cells(&self) -> impl Iterator<Item = Vec2> + '_ {
+        (self.start..self.end).map(|x| Vec2 { x, y: self.row })
+    }
+    fn neighbor_cells(&self) -> impl Iterator<Item = Vec2> + '_ {
+        (self.row - 1..=self.row + 1)
+            .flat_map(|y| (self.start - 1..=self.end).map(move |x| Vec2 { x, y }))
+            .filter(|pos| pos.y != self.row || pos.x < self.start || pos.x >= self.end)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    Product,
+    Sum,
+}
+
+/// Which symbols count as gears and how their numbers combine into a ratio
+#[derive(Debug, Clone)]
+pub struct GearRule {
+    pub symbols: Vec<char>,
+    pub min: usize,
+    pub max: usize,
+    pub aggregate: Aggregate,
+}
+
+impl Default for GearRule {
+    fn default() -> Self {
+        GearRule {
+            symbols: vec!['*'],
+            min: 2,
+            max: 2,
+            aggregate: Aggregate::Product,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Gear<'a> {
+    pub symbol: &'a Symbol,
+    pub numbers: Vec<&'a Number>,
+    /// Products of several ten digit numbers outgrow a u64
+    pub ratio: BigUint,
+}
+
+impl Gear<'_> {
+    pub fn to_json(&self) -> String {
+        let numbers: Vec<String> = self.numbers.iter().map(|n| n.value.to_string()).collect();
+        format!(
+            r#"{{"symbol":{},"row":{},"col":{},"numbers":[{}],"ratio":{}}}"#,
+            crate::utils::json_string(&self.symbol.c.to_string()),
+            self.symbol.pos.y,
+            self.symbol.pos.x,
+            numbers.join(","),
+            self.ratio
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    Number(&'a str),
     Empty,
     Symbol(char),
 }
 
-impl fmt::Debug for Point {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let c = match self {
-            Point::Number { i: _, c, value: _ } => *c,
-            Point::Empty => '.',
-            Point::Symbol(s) => s.as_char(),
-        };
-        write!(f, "{}", c)
-    }
+fn token_parser(input: &str) -> IResult<&str, Token<'_>> {
+    alt((
+        map(digit1, Token::Number),
+        value(Token::Empty, tag(".")),
+        map(anychar, Token::Symbol),
+    ))(input)
 }
 
-fn parse() -> crate::utils::Map<Point> {
-    let mut schematic = crate::utils::Map {
-        size: crate::utils::Vec2 { x: 0, y: 0 },
-        elements: vec![],
-    };
-    let mut i = 0usize;
-    if let Ok(lines) = crate::utils::read_lines("src/inputs/day3.txt") {
-        for line in lines {
-            if let Ok(chars) = line {
-                let points = parse_line(&mut i, &chars).unwrap().1;
-                schematic.size.y += 1;
-                schematic.size.x = points.len() as i64;
-                schematic.elements.extend(points);
+#[derive(Debug, Default)]
+pub struct Schematic {
+    pub numbers: Vec<Number>,
+    pub symbols: Vec<Symbol>,
+    // Cell to number id, every digit of a number points at the same id
+    number_cells: HashMap<Vec2, usize>,
+    symbol_cells: HashMap<Vec2, usize>,
+}
+
+impl Schematic {
+    fn parse_line(&mut self, row: i64, line: &str) -> Result<(), String> {
+        let mut rest = line;
+        while !rest.is_empty() {
+            let col = (line.len() - rest.len()) as i64;
+            let (remaining, token) = token_parser(rest).unwrap();
+            match token {
+                Token::Number(digits) => self.numbers.push(Number {
+                    id: self.numbers.len(),
+                    row,
+                    start: col,
+                    end: col + digits.len() as i64,
+                    value: digits.parse().map_err(|_| {
+                        format!(
+                            "line {}, column {}: {} is too large for a part number",
+                            row + 1,
+                            col + 1,
+                            digits
+                        )
+                    })?,
+                }),
+                Token::Symbol(c) => self.symbols.push(Symbol {
+                    pos: Vec2 { x: col, y: row },
+                    c,
+                }),
+                Token::Empty => {}
             }
+            rest = remaining;
+        }
+        Ok(())
+    }
+
+    /// Fails on a number that doesn't fit in a `u32`
+    pub fn parse(input: &str) -> Result<Schematic, String> {
+        let mut schematic = Schematic::default();
+        for (row, line) in input.lines().enumerate() {
+            schematic.parse_line(row as i64, line)?;
+        }
+        for n in schematic.numbers.iter() {
+            schematic
+                .number_cells
+                .extend(n.cells().map(|pos| (pos, n.id)));
+        }
+        for (i, s) in schematic.symbols.iter().enumerate() {
+            schematic.symbol_cells.insert(s.pos, i);
         }
+        Ok(schematic)
+    }
+
+    pub fn number_at(&self, pos: Vec2) -> Option<&Number> {
+        self.number_cells.get(&pos).map(|&id| &self.numbers[id])
+    }
+
+    pub fn symbol_at(&self, pos: Vec2) -> Option<&Symbol> {
+        self.symbol_cells.get(&pos).map(|&i| &self.symbols[i])
+    }
+
+    /// Each number touching `symbol` once, even when several of its digits do
+    pub fn numbers_adjacent_to(&self, symbol: &Symbol) -> Vec<&Number> {
+        let mut ids: Vec<usize> = (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter_map(|(dx, dy)| {
+                self.number_cells.get(&Vec2 {
+                    x: symbol.pos.x + dx,
+                    y: symbol.pos.y + dy,
+                })
+            })
+            .copied()
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids.into_iter().map(|id| &self.numbers[id]).collect()
+    }
+
+    pub fn symbols_adjacent_to(&self, number: &Number) -> Vec<&Symbol> {
+        number
+            .neighbor_cells()
+            .filter_map(|pos| self.symbol_cells.get(&pos))
+            .map(|&i| &self.symbols[i])
+            .collect()
+    }
+
+    pub fn gears(&self, rule: &GearRule) -> Vec<Gear<'_>> {
+        self.symbols
+            .iter()
+            .filter(|s| rule.symbols.contains(&s.c))
+            .map(|symbol| (symbol, self.numbers_adjacent_to(symbol)))
+            .filter(|(_, numbers)| (rule.min..=rule.max).contains(&numbers.len()))
+            .map(|(symbol, numbers)| {
+                let values = numbers.iter().map(|n| BigUint::from(n.value));
+                let ratio = match rule.aggregate {
+                    Aggregate::Product => values.product(),
+                    Aggregate::Sum => values.sum(),
+                };
+                Gear {
+                    symbol,
+                    numbers,
+                    ratio,
+                }
+            })
+            .collect()
+    }
+
+    pub fn part_numbers(&self) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .filter(|n| !self.symbols_adjacent_to(n).is_empty())
+    }
+
+    pub fn isolated_numbers(&self) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .filter(|n| self.symbols_adjacent_to(n).is_empty())
     }
-    schematic
 }
 
-fn parse_line<'a>(i: &mut usize, input: &'a str) -> IResult<&'a str, Vec<Point>> {
-    map(
-        many1(alt((
-            map(digit1, |d: &str| {
-                let value = u32::from_str_radix(d, 10).unwrap();
-                *i += 1;
-                d.chars()
-                    .map(|c| Point::Number { i: *i, c, value })
-                    .collect()
-            }),
-            value(vec![Point::Empty], tag(".")),
-            map(take(1usize), |c: &str| {
-                vec![Point::Symbol(c.chars().next().unwrap())]
-            }),
-        ))),
-        |res| res.into_iter().flatten().collect::<Vec<Point>>(),
-    )(input)
+pub struct Day3;
+
+impl crate::runner::Solution for Day3 {
+    type Options = GearRule;
+    type Parsed<'a> = (Schematic, GearRule);
+
+    fn options(args: &crate::cli::Args) -> Result<Self::Options, String> {
+        crate::cli::gear_rule(args)
+    }
+
+    fn parse<'a>(input: &'a str, rule: &Self::Options) -> Result<Self::Parsed<'a>, String> {
+        Ok((Schematic::parse(input)?, rule.clone()))
+    }
+
+    fn part1((schematic, _): &Self::Parsed<'_>) -> Result<String, String> {
+        Ok(schematic
+            .part_numbers()
+            .map(|n| n.value as u64)
+            .sum::<u64>()
+            .to_string())
+    }
+
+    fn part2((schematic, rule): &Self::Parsed<'_>) -> Result<String, String> {
+        Ok(schematic
+            .gears(rule)
+            .into_iter()
+            .map(|g| g.ratio)
+            .sum::<BigUint>()
+            .to_string())
+    }
 }
 
-pub fn main() {
-    let schematic = dbg!(parse());
-
-    let mut ans1 = 0;
-    let mut ans2 = 0;
-
-    let mut added_numbers = HashSet::new();
-    for el in schematic.iter() {
-        if let Point::Symbol(s) = el.1 {
-            let mut gear_numbers = HashSet::new();
-            let mut gear_ratio = 1;
-            for n in schematic.neighbor_elements(el.0) {
-                if let Point::Number { i, c: _, value } = n {
-                    if added_numbers.insert(i) {
-                        ans1 += value;
-                    }
-                    if gear_numbers.insert(i) {
-                        gear_ratio *= value;
-                    }
-                }
-            }
-            if s == '*' && gear_numbers.len() == 2 {
-                ans2 += gear_ratio
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::Solution;
+
+    const EXAMPLE: &str = include_str!("inputs/day3.example.txt");
+
+    fn values(numbers: &[&Number]) -> Vec<u32> {
+        numbers.iter().map(|n| n.value).collect()
+    }
+
+    #[test]
+    fn large_numbers() {
+        let grid = "4294967295*4294967295\n4294967295..........";
+        let rule = GearRule {
+            min: 3,
+            max: 3,
+            ..GearRule::default()
+        };
+        let parsed = Day3::parse(grid, &rule).unwrap();
+        assert_eq!(Day3::part1(&parsed), Ok((3 * u32::MAX as u64).to_string()));
+        assert_eq!(
+            Day3::part2(&parsed),
+            Ok(BigUint::from(u32::MAX).pow(3).to_string())
+        );
+        assert_eq!(
+            Schematic::parse("1.\n.*99999999999").err(),
+            Some("line 2, column 3: 99999999999 is too large for a part number".into())
+        );
+    }
+
+    #[test]
+    fn example() {
+        let parsed = Day3::parse(EXAMPLE, &GearRule::default()).unwrap();
+        assert_eq!(Day3::part1(&parsed), Ok("4361".into()));
+        assert_eq!(Day3::part2(&parsed), Ok("467835".into()));
+    }
+
+    #[test]
+    fn ids() {
+        let schematic = Schematic::parse(EXAMPLE).unwrap();
+        assert_eq!(schematic.numbers.len(), 10);
+        for (i, n) in schematic.numbers.iter().enumerate() {
+            assert_eq!(n.id, i);
+            for x in n.start..n.end {
+                assert_eq!(schematic.number_at(Vec2 { x, y: n.row }), Some(n));
             }
         }
+        let isolated: Vec<u32> = schematic.isolated_numbers().map(|n| n.value).collect();
+        assert_eq!(isolated, [114, 58]);
+    }
+
+    #[test]
+    fn numbers_at_line_ends() {
+        let schematic = Schematic::parse("....12\n.....*\n12....").unwrap();
+        let gear = &schematic.symbols[0];
+        assert_eq!(values(&schematic.numbers_adjacent_to(gear)), [12]);
+        assert_eq!(schematic.part_numbers().count(), 1);
+        assert_eq!(schematic.numbers[0].end, 6);
+    }
+
+    #[test]
+    fn numbers_on_the_border() {
+        let schematic = Schematic::parse("1*\n..\n*9").unwrap();
+        assert_eq!(
+            values(&schematic.part_numbers().collect::<Vec<_>>()),
+            [1, 9]
+        );
+        let schematic = Schematic::parse("123\n...").unwrap();
+        assert_eq!(schematic.part_numbers().count(), 0);
+    }
+
+    #[test]
+    fn equal_values_are_different_numbers() {
+        let schematic = Schematic::parse("2.2\n.*.\n...").unwrap();
+        let gears = schematic.gears(&GearRule::default());
+        assert_eq!(gears.len(), 1);
+        assert_eq!(values(&gears[0].numbers), [2, 2]);
+        assert_eq!(gears[0].ratio, 4_u32.into());
+    }
+
+    #[test]
+    fn each_number_counted_once() {
+        // All three digits of 123 touch the symbol below, as do both digits of 45
+        let schematic = Schematic::parse("123\n.*.\n45.").unwrap();
+        let symbol = &schematic.symbols[0];
+        assert_eq!(values(&schematic.numbers_adjacent_to(symbol)), [123, 45]);
+        assert_eq!(
+            schematic.symbols_adjacent_to(&schematic.numbers[0]).len(),
+            1
+        );
+        let parsed = Day3::parse("123\n.*.\n45.", &GearRule::default()).unwrap();
+        assert_eq!(Day3::part1(&parsed), Ok("168".into()));
+        assert_eq!(Day3::part2(&parsed), Ok("5535".into()));
     }
-    println!("part1: {}, part2: {}", ans1, ans2);
 }
@@ -1,19 +1,74 @@
 use nom::{
     bytes::complete::tag,
-    character::complete::u16,
     character::complete::{space1, u32},
     combinator::map,
     multi::many1,
     sequence::{delimited, preceded, separated_pair, tuple},
     IResult,
 };
-use std::{collections::HashSet, fmt};
+use num::{BigUint, One, Zero};
+use std::{collections::HashSet, fmt, str::FromStr};
+
+/// Sparse bitset keeping only the non-empty 64 bit words, sorted by word index. Small numbers
+/// fit in a couple of words while wide ranges cost no more than the numbers themselves
+#[derive(Clone, Default, PartialEq)]
+pub struct BitSet {
+    words: Vec<(u32, u64)>,
+}
+
+impl FromIterator<u32> for BitSet {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let mut nums: Vec<u32> = iter.into_iter().collect();
+        nums.sort_unstable();
+        let mut words: Vec<(u32, u64)> = vec![];
+        for n in nums {
+            match words.last_mut() {
+                Some((index, word)) if *index == n / 64 => *word |= 1 << (n % 64),
+                _ => words.push((n / 64, 1 << (n % 64))),
+            }
+        }
+        BitSet { words }
+    }
+}
+
+impl BitSet {
+    pub fn intersection_count(&self, other: &BitSet) -> usize {
+        let (mut a, mut b) = (self.words.iter().peekable(), other.words.iter().peekable());
+        let mut count = 0;
+        while let (Some(&&(i, x)), Some(&&(j, y))) = (a.peek(), b.peek()) {
+            if i <= j {
+                a.next();
+            }
+            if j <= i {
+                b.next();
+            }
+            if i == j {
+                count += (x & y).count_ones() as usize;
+            }
+        }
+        count
+    }
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.words.iter().flat_map(|&(index, word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| index * 64 + bit)
+        })
+    }
+}
+
+impl fmt::Debug for BitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
 
 #[derive(Clone)]
-struct Card {
+pub struct Card {
     i: u32,
-    w: HashSet<u16>,
-    n: HashSet<u16>,
+    w: BitSet,
+    n: BitSet,
+    matches: usize,
 }
 impl fmt::Debug for Card {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -23,24 +78,108 @@ impl fmt::Debug for Card {
 }
 
 impl Card {
+    fn new(i: u32, w: impl IntoIterator<Item = u32>, n: impl IntoIterator<Item = u32>) -> Card {
+        let w: BitSet = w.into_iter().collect();
+        let n: BitSet = n.into_iter().collect();
+        let matches = w.intersection_count(&n);
+        Card { i, w, n, matches }
+    }
     fn count(&self) -> usize {
-        self.w.intersection(&self.n).count()
-    }
-    fn points(&self) -> u64 {
-        let count = self.count();
-        if count == 0 {
-            0
-        } else {
-            2_u64.pow((count - 1) as u32)
+        self.matches
+    }
+    fn points(&self, scoring: Scoring) -> BigUint {
+        scoring.points(self.count())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Scoring {
+    /// 1, 2, 4, 8, ... the puzzle's rule
+    #[default]
+    Doubling,
+    /// A point per match
+    Linear,
+    /// 1, 2, 3, 5, 8, ...
+    Fibonacci,
+    /// Doubling, but never more than the cap
+    Capped(u64),
+}
+
+impl Scoring {
+    /// Doubling and Fibonacci outgrow a u64 past 64 matches, a capped score is capped before it
+    /// can overflow
+    pub fn points(self, matches: usize) -> BigUint {
+        match (self, matches) {
+            (_, 0) => BigUint::zero(),
+            (Scoring::Doubling, m) => BigUint::one() << (m - 1),
+            (Scoring::Linear, m) => m.into(),
+            (Scoring::Fibonacci, m) => {
+                (1..m)
+                    .fold((BigUint::one(), BigUint::one()), |(a, b), _| {
+                        let next = &a + &b;
+                        (b, next)
+                    })
+                    .1
+            }
+            (Scoring::Capped(cap), m) => u32::try_from(m - 1)
+                .ok()
+                .and_then(|e| 2_u64.checked_pow(e))
+                .map_or(cap, |p| p.min(cap))
+                .into(),
         }
     }
 }
 
-fn sets_parser(input: &str) -> IResult<&str, (Vec<u16>, Vec<u16>)> {
+impl FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "doubling" => Ok(Scoring::Doubling),
+            "linear" => Ok(Scoring::Linear),
+            "fibonacci" => Ok(Scoring::Fibonacci),
+            s => match s.strip_prefix("capped:").map(u64::from_str) {
+                Some(Ok(cap)) => Ok(Scoring::Capped(cap)),
+                _ => Err(format!("Unknown scoring '{}'", s)),
+            },
+        }
+    }
+}
+
+/// Which cards a winning card hands out copies of
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Copies {
+    /// The next `matches` cards, the puzzle's rule
+    #[default]
+    Next,
+    /// The `matches` cards before it
+    Previous,
+}
+
+impl FromStr for Copies {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "next" => Ok(Copies::Next),
+            "previous" => Ok(Copies::Previous),
+            s => Err(format!("Unknown copy rule '{}'", s)),
+        }
+    }
+}
+
+/// How cards score in part 1 and hand out copies in part 2
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rules {
+    pub scoring: Scoring,
+    pub copies: Copies,
+}
+
+fn sets_parser(input: &str) -> IResult<&str, (Vec<u32>, Vec<u32>)> {
     separated_pair(
-        many1(preceded(space1, u16)),
+        many1(preceded(space1, u32)),
         tag(" |"),
-        many1(preceded(space1, u16)),
+        many1(preceded(space1, u32)),
     )(input)
 }
 
@@ -50,31 +189,255 @@ fn card_parser(input: &str) -> IResult<&str, Card> {
             delimited(tuple((tag("Card"), space1)), u32, tag(":")),
             sets_parser,
         )),
-        |(i, (w, n))| Card {
-            i,
-            w: w.into_iter().collect(),
-            n: n.into_iter().collect(),
-        },
+        |(i, (w, n))| Card::new(i, w, n),
     )(input.trim())
 }
 
-pub fn main() {
-    let mut ans1 = 0;
-    let mut card_counts = vec![];
-    if let Ok(lines) = crate::utils::read_lines("src/inputs/day4.txt") {
-        for line in lines {
-            if let Ok(chars) = line {
-                let (_rest, card) = card_parser(&chars).unwrap();
-                ans1 += card.points();
-                card_counts.push((card, 1_u64));
-            }
+/// Times matching a synthetic deck of numbers in `1..=range` with hash sets against bitsets,
+/// returning both durations. Fails if the two disagree on the matches
+pub fn bench(
+    cards: usize,
+    range: u32,
+) -> Result<(std::time::Duration, std::time::Duration), String> {
+    let mut rng = crate::utils::XorShift::default();
+    let mut rand = || rng.within(&(1..range as u64 + 1)) as u32;
+    let deck: Vec<(Vec<u32>, Vec<u32>)> = (0..cards)
+        .map(|_| {
+            (
+                (0..10).map(|_| rand()).collect(),
+                (0..25).map(|_| rand()).collect(),
+            )
+        })
+        .collect();
+
+    let start = std::time::Instant::now();
+    let hashed: usize = deck
+        .iter()
+        .map(|(w, n)| {
+            let w: HashSet<u32> = w.iter().copied().collect();
+            let n: HashSet<u32> = n.iter().copied().collect();
+            w.intersection(&n).count()
+        })
+        .sum();
+    let hashset = start.elapsed();
+
+    let start = std::time::Instant::now();
+    let bits: usize = deck
+        .iter()
+        .enumerate()
+        .map(|(i, (w, n))| Card::new(i as u32, w.iter().copied(), n.iter().copied()).count())
+        .sum();
+    let bitset = start.elapsed();
+
+    if hashed != bits {
+        return Err(format!(
+            "Hash sets found {} matches, bitsets {}",
+            hashed, bits
+        ));
+    }
+    Ok((hashset, bitset))
+}
+
+/// How many copies of a card ended up in the pile, and how many of those were won from each
+/// earlier card. The original is the one copy not accounted for in `won_from`
+#[derive(Debug, Clone)]
+pub struct Cascaded {
+    pub card: u32,
+    pub matches: usize,
+    pub copies: BigUint,
+    pub won_from: Vec<(u32, BigUint)>,
+}
+
+impl Cascaded {
+    pub fn to_json(&self) -> String {
+        let won_from: Vec<String> = self
+            .won_from
+            .iter()
+            .map(|(card, copies)| format!(r#"{{"card":{},"copies":{}}}"#, card, copies))
+            .collect();
+        format!(
+            r#"{{"card":{},"matches":{},"copies":{},"won_from":[{}]}}"#,
+            self.card,
+            self.matches,
+            self.copies,
+            won_from.join(",")
+        )
+    }
+}
+
+/// Every copy of a card wins a copy of the `matches` cards next to it, cards past either end of
+/// the table can't be won. Cards are visited in the direction copies flow, so each card's count is
+/// final before it hands out its own copies. `won` hears of every card winning copies of another.
+/// Copies grow exponentially when they flow backwards, so they are counted without a bound
+fn spread(
+    cards: &[Card],
+    rule: Copies,
+    mut won: impl FnMut(usize, usize, &BigUint),
+) -> Vec<BigUint> {
+    let mut copies = vec![BigUint::one(); cards.len()];
+    let order: Box<dyn Iterator<Item = usize>> = match rule {
+        Copies::Next => Box::new(0..cards.len()),
+        Copies::Previous => Box::new((0..cards.len()).rev()),
+    };
+    for i in order {
+        let matches = cards[i].count();
+        let targets = match rule {
+            Copies::Next => i + 1..(i + 1 + matches).min(cards.len()),
+            Copies::Previous => i.saturating_sub(matches)..i,
+        };
+        let winner = copies[i].clone();
+        for j in targets {
+            copies[j] += &winner;
+            won(j, i, &winner);
+        }
+    }
+    copies
+}
+
+/// How many copies of each card end up in the pile
+pub fn copies(cards: &[Card], rule: Copies) -> Vec<BigUint> {
+    spread(cards, rule, |_, _, _| {})
+}
+
+/// The pile with where every card's copies were won from
+pub fn cascade(cards: &[Card], rule: Copies) -> Vec<Cascaded> {
+    let mut won_from = vec![vec![]; cards.len()];
+    let copies = spread(cards, rule, |won, from, copies| {
+        won_from[won].push((cards[from].i, copies.clone()))
+    });
+    cards
+        .iter()
+        .zip(copies)
+        .zip(won_from)
+        .map(|((c, copies), won_from)| Cascaded {
+            card: c.i,
+            matches: c.count(),
+            copies,
+            won_from,
+        })
+        .collect()
+}
+
+pub struct Day4;
+
+impl crate::runner::Solution for Day4 {
+    type Options = Rules;
+    type Parsed<'a> = (Vec<Card>, Rules);
+
+    fn options(args: &crate::cli::Args) -> Result<Self::Options, String> {
+        crate::cli::card_rules(args)
+    }
+
+    fn parse<'a>(input: &'a str, rules: &Self::Options) -> Result<Self::Parsed<'a>, String> {
+        let cards = input
+            .lines()
+            .map(|chars| card_parser(chars).unwrap().1)
+            .collect();
+        Ok((cards, *rules))
+    }
+
+    fn part1((cards, rules): &Self::Parsed<'_>) -> Result<String, String> {
+        let ans: BigUint = cards.iter().map(|card| card.points(rules.scoring)).sum();
+        Ok(ans.to_string())
+    }
+
+    fn part2((cards, rules): &Self::Parsed<'_>) -> Result<String, String> {
+        let ans: BigUint = copies(cards, rules.copies).into_iter().sum();
+        Ok(ans.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::Solution;
+
+    const EXAMPLE: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 43 72
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    #[test]
+    fn example() {
+        let parsed = Day4::parse(EXAMPLE, &Rules::default()).unwrap();
+        assert_eq!(Day4::part1(&parsed), Ok("13".into()));
+        assert_eq!(Day4::part2(&parsed), Ok("30".into()));
+    }
+
+    #[test]
+    fn scoring() {
+        let points = |scoring: Scoring| (0..7).map(|m| scoring.points(m)).collect::<Vec<_>>();
+        let expected = |p: [u32; 7]| p.map(BigUint::from).to_vec();
+        assert_eq!(points(Scoring::Doubling), expected([0, 1, 2, 4, 8, 16, 32]));
+        assert_eq!(points(Scoring::Linear), expected([0, 1, 2, 3, 4, 5, 6]));
+        assert_eq!(points(Scoring::Fibonacci), expected([0, 1, 2, 3, 5, 8, 13]));
+        assert_eq!(points(Scoring::Capped(5)), expected([0, 1, 2, 4, 5, 5, 5]));
+    }
+
+    #[test]
+    fn scoring_past_u64() {
+        assert_eq!(Scoring::Doubling.points(65), BigUint::one() << 64);
+        assert_eq!(Scoring::Capped(u64::MAX).points(65), u64::MAX.into());
+        assert_eq!(Scoring::Capped(7).points(1000), 7_u32.into());
+        assert_eq!(
+            Scoring::Fibonacci.points(100),
+            "573147844013817084101".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn traces() {
+        let (cards, _) = Day4::parse(EXAMPLE, &Rules::default()).unwrap();
+        let pile = cascade(&cards, Copies::Next);
+        assert_eq!(pile.len(), 6);
+        for (c, copies) in pile.iter().zip(copies(&cards, Copies::Next)) {
+            let won: BigUint = c.won_from.iter().map(|(_, n)| n).sum();
+            assert_eq!(won + 1_u32, copies, "card {}", c.card);
+            assert_eq!(c.copies, copies, "card {}", c.card);
         }
+        // Card 4 is won once by the original card 1, twice by card 2 and four times by card 3
+        let card4 = &pile[3];
+        let won_from: Vec<(u32, u32)> = card4
+            .won_from
+            .iter()
+            .map(|(card, n)| (*card, u32::try_from(n).unwrap()))
+            .collect();
+        assert_eq!((card4.card, card4.matches), (4, 1));
+        assert_eq!(won_from, [(1, 1), (2, 2), (3, 4)]);
+        assert_eq!(
+            card4.to_json(),
+            r#"{"card":4,"matches":1,"copies":8,"won_from":[{"card":1,"copies":1},{"card":2,"copies":2},{"card":3,"copies":4}]}"#
+        );
+        assert_eq!(
+            pile[0].to_json(),
+            r#"{"card":1,"matches":4,"copies":1,"won_from":[]}"#
+        );
     }
-    for i in 0..card_counts.len() {
-        for ii in 1..(card_counts[i].0.count() + 1) {
-            card_counts[i + ii].1 += card_counts[i].1;
+
+    #[test]
+    fn copies_flow_backwards() {
+        let (cards, _) = Day4::parse(EXAMPLE, &Rules::default()).unwrap();
+        let copies = copies(&cards, Copies::Previous);
+        // Card 4 wins a card 3, both copies of card 3 win cards 1 and 2, card 2's three copies win
+        // three more card 1s
+        assert_eq!(copies, [6_u32, 3, 2, 1, 1, 1].map(BigUint::from));
+    }
+
+    #[test]
+    fn many_copies_flow_backwards() {
+        // Every card wins all the cards before it, so card i ends up with 2^(n-1-i) copies
+        let cards: Vec<Card> = (0..100).map(|i| Card::new(i, 0..100, 0..100)).collect();
+        let pile = copies(&cards, Copies::Previous);
+        assert_eq!(pile[0], BigUint::one() << 99);
+        assert_eq!(pile[99], BigUint::one());
+    }
+
+    #[test]
+    fn bitsets_match_hash_sets() {
+        for range in [1, 10, 64, 100, 1000, 100_000] {
+            assert!(bench(2000, range).is_ok(), "range {}", range);
         }
     }
-    let ans2 = card_counts.iter().fold(0, |acc, card| acc + card.1);
-    println!("part1: {}, part2: {}", ans1, ans2);
 }
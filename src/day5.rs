@@ -1,92 +1,280 @@
-use std::{iter::once, ops::Range};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    ops::Range,
+};
 
 use nom::{
     bytes::complete::tag,
-    character::complete::{line_ending, not_line_ending, space1, u64},
+    character::complete::{alpha1, line_ending, space1, u64},
     combinator::map,
     multi::{many0, many1, separated_list1},
-    sequence::{preceded, tuple},
+    sequence::{delimited, preceded, separated_pair, tuple},
     IResult,
 };
 
-trait RangeExt {
-    fn contains_range(&self, other: &Self) -> bool;
-    fn start_overlap(&self, other: &Self) -> bool;
-    fn end_overlap(&self, other: &Self) -> bool;
+use crate::utils::IntervalMap;
+
+/// The `source-to-destination map:` section of the almanac
+#[derive(Debug, Clone)]
+pub struct Mapping {
+    pub source: String,
+    pub destination: String,
+    map: IntervalMap,
 }
 
-impl RangeExt for std::ops::Range<u64> {
-    fn contains_range(&self, other: &Self) -> bool {
-        other.start >= self.start && other.end <= self.end
+impl Mapping {
+    fn name(&self) -> String {
+        format!("{}-to-{}", self.source, self.destination)
+    }
+    fn map(self: &Mapping, i: u64) -> u64 {
+        self.map.get(i)
+    }
+    fn preimage(&self, i: u64) -> Vec<u64> {
+        self.map.preimage(i)
     }
+    fn map_ranges(self: &Mapping, ranges_in: Vec<Range<u64>>) -> Vec<Range<u64>> {
+        self.map.map_ranges(&ranges_in)
+    }
+    /// Seeds worth checking in `range`: its ends and both sides of every source range boundary
+    fn boundaries(&self, range: &Range<u64>) -> Vec<u64> {
+        let mut points = vec![range.start, range.end - 1];
+        for (source, _) in self.map.ranges() {
+            for p in [source.start, source.end] {
+                points.extend(
+                    [p.saturating_sub(1), p]
+                        .iter()
+                        .filter(|p| range.contains(p)),
+                );
+            }
+        }
+        points
+    }
+}
+
+/// The whole chain of mappings as a single map from its first category to its last
+pub fn compose(mappings: &[&Mapping]) -> IntervalMap {
+    mappings
+        .iter()
+        .fold(IntervalMap::default(), |acc, m| acc.then(&m.map))
+}
+
+/// Every seed ending up at `location`
+pub fn seeds_for(mappings: &[&Mapping], location: u64) -> Vec<u64> {
+    compose(mappings).preimage(location)
+}
 
-    fn start_overlap(&self, other: &Self) -> bool {
-        other.start < self.start && other.end >= self.start
+/// Why `seeds` can't be paired into part 2's ranges: a seed left without a length, or a range
+/// ending past `u64::MAX`
+fn seed_problems(seeds: &[u64]) -> Vec<String> {
+    let mut problems = vec![];
+    if seeds.len() % 2 == 1 {
+        problems.push(format!(
+            "{} seeds can't all be paired into ranges, {} has no length",
+            seeds.len(),
+            seeds[seeds.len() - 1]
+        ));
     }
+    for pair in seeds.chunks_exact(2) {
+        if pair[0].checked_add(pair[1]).is_none() {
+            problems.push(format!(
+                "seed range {} + {} overflows u64",
+                pair[0], pair[1]
+            ));
+        }
+    }
+    problems
+}
 
-    fn end_overlap(&self, other: &Self) -> bool {
-        other.start < self.end && other.end >= self.end
+/// The seeds read as `start length` pairs, part 1 reads them one by one and doesn't need this
+pub fn seed_ranges(seeds: &[u64]) -> Result<Vec<Range<u64>>, String> {
+    let problems = seed_problems(seeds);
+    if !problems.is_empty() {
+        return Err(problems.join("\n"));
     }
+    Ok(seeds
+        .chunks(2)
+        .map(|nums| nums[0]..nums[0] + nums[1])
+        .collect())
 }
 
-#[derive(Debug, Clone)]
-struct Mapping {
-    _categories: String,
-    ranges: Vec<(u64, Range<u64>)>,
+fn covers(ranges: &[Range<u64>], i: u64) -> bool {
+    let index = ranges.partition_point(|r| r.end <= i);
+    ranges.get(index).is_some_and(|r| r.contains(&i))
 }
 
-impl Mapping {
-    fn map(self: &Mapping, i: u64) -> u64 {
-        for (dest, range) in self.ranges.iter() {
-            if range.contains(&i) {
-                return dest + i - range.start;
-            }
-            if i < range.start {
-                return i;
+/// Compares mapping the seed ranges as ranges with mapping seeds one at a time, returning how many
+/// seeds were compared. Up to `exhaustive` seeds per step every seed is mapped, beyond that only
+/// the range ends, the seeds around each boundary and `samples` pseudo random seeds per range
+pub fn check(
+    seeds: &[u64],
+    mappings: &[&Mapping],
+    exhaustive: u64,
+    samples: usize,
+) -> Result<usize, String> {
+    let mut rng = crate::utils::XorShift::default();
+    let planted = crate::utils::merge_ranges(seed_ranges(seeds)?);
+    let mut ranges = planted.clone();
+    let mut checked = 0;
+    for m in mappings {
+        let mapped = m.map_ranges(ranges.clone());
+        let total: u64 = ranges.iter().map(|r| r.end - r.start).sum();
+        let probes: Vec<u64> = match total <= exhaustive {
+            true => ranges.iter().cloned().flatten().collect(),
+            false => ranges
+                .iter()
+                .flat_map(|r| {
+                    let mut points = m.boundaries(r);
+                    points.extend((0..samples).map(|_| rng.within(r)));
+                    points
+                })
+                .collect(),
+        };
+        if let Some(p) = probes.iter().find(|&&p| !covers(&mapped, m.map(p))) {
+            return Err(format!(
+                "{}: {} maps to {}, which is not in {:?}",
+                m.name(),
+                p,
+                m.map(*p),
+                mapped
+            ));
+        }
+        if let Some(p) = probes.iter().find(|&&p| !m.preimage(m.map(p)).contains(&p)) {
+            return Err(format!(
+                "{}: {} maps to {}, but {} maps back to {:?}",
+                m.name(),
+                p,
+                m.map(*p),
+                m.map(*p),
+                m.preimage(m.map(*p))
+            ));
+        }
+        if total <= exhaustive {
+            let mut images: Vec<u64> = probes.iter().map(|&p| m.map(p)).collect();
+            images.sort_unstable();
+            images.dedup();
+            let len: u64 = mapped.iter().map(|r| r.end - r.start).sum();
+            if len != images.len() as u64 {
+                return Err(format!(
+                    "{}: {:?} holds {} seeds but only {} are mapped onto it",
+                    m.name(),
+                    mapped,
+                    len,
+                    images.len()
+                ));
             }
         }
-        i
+        checked += probes.len();
+        ranges = mapped;
     }
-    fn map_range(self: &Mapping, range_in: Range<u64>) -> Vec<Range<u64>> {
-        for (dest, range) in self.ranges.iter() {
-            if range.contains_range(&range_in) {
-                return vec![Range {
-                    start: range_in.start - range.start + dest,
-                    end: range_in.end - range.start + dest,
-                }];
-            }
-            if range.start_overlap(&range_in) {
-                return vec![
-                    Range {
-                        start: range_in.start,
-                        end: range.start,
-                    },
-                    Range {
-                        start: *dest,
-                        end: range_in.end - range.start + dest,
-                    },
-                ];
+    // Walking back from the lowest location, nothing below it may come from a planted seed
+    let composed = compose(mappings);
+    let lowest = ranges.first().map_or(0, |r| r.start);
+    let below = crate::utils::intersect_ranges(&composed.preimage_range(0..lowest), &planted);
+    if !below.is_empty() {
+        return Err(format!("Seeds {:?} map below location {}", below, lowest));
+    }
+    if !composed
+        .preimage(lowest)
+        .iter()
+        .any(|&s| covers(&planted, s))
+    {
+        return Err(format!("No planted seed maps to location {}", lowest));
+    }
+    Ok(checked)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Problems with the seeds line, they only keep part 2 from running
+fn validate_seeds(input: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for (i, text) in input.lines().enumerate() {
+        if let Some(seeds) = text.trim().strip_prefix("seeds:") {
+            let problems = match seeds
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<Vec<u64>, _>>()
+            {
+                Ok(seeds) => seed_problems(&seeds),
+                Err(_) => vec![format!("expected seed numbers, got '{}'", seeds.trim())],
+            };
+            diagnostics.extend(problems.into_iter().map(|message| Diagnostic {
+                line: i + 1,
+                message,
+            }));
+        }
+    }
+    diagnostics
+}
+
+/// Problems with the map sections, no part can be solved with them
+fn validate_maps(input: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let mut report = |line: usize, message: String| diagnostics.push(Diagnostic { line, message });
+    // Source ranges of the current section with the line they came from
+    let mut sources: Vec<(usize, Range<u128>)> = vec![];
+    for (i, text) in input.lines().enumerate() {
+        let (line, text) = (i + 1, text.trim());
+        if text.starts_with("seeds:") {
+            continue;
+        } else if text.ends_with("map:") {
+            sources.clear();
+        } else if !text.is_empty() {
+            let nums: Result<Vec<u64>, _> = text.split_whitespace().map(str::parse).collect();
+            let [dest, source, len] = match nums.as_deref() {
+                Ok(&[dest, source, len]) => [dest, source, len],
+                _ => {
+                    report(
+                        line,
+                        format!("expected destination, source and length, got '{}'", text),
+                    );
+                    continue;
+                }
+            };
+            for (what, start) in [("destination", dest), ("source", source)] {
+                if start.checked_add(len).is_none() {
+                    report(
+                        line,
+                        format!("{} {} + length {} overflows u64", what, start, len),
+                    );
+                }
             }
-            if range.end_overlap(&range_in) {
-                return once(Range {
-                    start: dest + range_in.start - range.start,
-                    end: dest + range.end - range.start,
-                })
-                .chain(self.map_range(Range {
-                    start: range.end,
-                    end: range_in.end,
-                }))
-                .collect::<Vec<_>>();
+            let range = source as u128..source as u128 + len as u128;
+            for (other, r) in sources.iter() {
+                if range.start < r.end && r.start < range.end {
+                    report(
+                        line,
+                        format!(
+                            "source {}..{} overlaps {}..{} from line {}",
+                            range.start, range.end, r.start, r.end, other
+                        ),
+                    );
+                }
             }
+            sources.push((line, range));
         }
-        vec![range_in]
-    }
-    fn map_ranges(self: &Mapping, ranges_in: Vec<Range<u64>>) -> Vec<Range<u64>> {
-        ranges_in
-            .into_iter()
-            .flat_map(|r| self.map_range(r))
-            .collect()
     }
+    diagnostics
+}
+
+/// Problems the solver would trip over or silently ignore: overlapping source ranges within a
+/// section, an odd number of seeds and ranges ending past `u64::MAX`
+pub fn validate(input: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = validate_seeds(input);
+    diagnostics.extend(validate_maps(input));
+    diagnostics.sort_by_key(|d| d.line);
+    diagnostics
 }
 
 fn parse_seeds(input: &str) -> IResult<&str, Vec<u64>> {
@@ -104,52 +292,400 @@ fn parse_point(input: &str) -> IResult<&str, (u64, Range<u64>)> {
         )
     })(input)
 }
+fn parse_categories(input: &str) -> IResult<&str, (&str, &str)> {
+    delimited(
+        many0(line_ending),
+        separated_pair(alpha1, tag("-to-"), alpha1),
+        tag(" map:"),
+    )(input)
+}
 fn parse_mapping(input: &str) -> IResult<&str, Vec<Mapping>> {
     many1(map(
-        tuple((
-            preceded(many0(line_ending), not_line_ending),
-            many1(parse_point),
-        )),
-        |(c, p)| Mapping {
-            _categories: c.into(),
-            ranges: p,
+        tuple((parse_categories, many1(parse_point))),
+        |((source, destination), p)| Mapping {
+            source: source.into(),
+            destination: destination.into(),
+            map: p
+                .into_iter()
+                .fold(IntervalMap::default(), |mut map, (dest, range)| {
+                    map.insert(range, dest).unwrap();
+                    map
+                }),
         },
     ))(input.trim())
 }
 
-pub fn main() {
-    let input = include_str!("inputs/day5.txt");
-    let (rest, seeds1) = parse_seeds(input).unwrap();
-    let (_rest, mut mappings) = parse_mapping(rest).unwrap();
-    for m in mappings.iter_mut() {
-        m.ranges.sort_by_key(|a| a.1.start)
+/// The seeds and the mappings between categories, each mapping is an edge of a graph from its
+/// source category to its destination
+#[derive(Debug, Clone)]
+pub struct Almanac {
+    pub seeds: Vec<u64>,
+    pub mappings: Vec<Mapping>,
+}
+
+impl Almanac {
+    fn edges<'a>(&'a self, from: &'a str) -> impl Iterator<Item = &'a Mapping> {
+        self.mappings.iter().filter(move |m| m.source == from)
     }
 
-    let ans1 = seeds1
-        .iter()
-        .map(|s| mappings.iter().fold(*s, |acc, v| v.map(acc)))
-        .min()
-        .unwrap();
+    /// The shortest run of mappings taking `from` values to `to` values. Once `validate_chain` has
+    /// passed it is also the only one
+    pub fn chain<'a>(&'a self, from: &'a str, to: &'a str) -> Result<Vec<&'a Mapping>, String> {
+        let mut via: HashMap<&str, &Mapping> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(category) = queue.pop_front() {
+            for m in self.edges(category) {
+                if m.destination != from && !via.contains_key(m.destination.as_str()) {
+                    via.insert(&m.destination, m);
+                    queue.push_back(&m.destination);
+                }
+            }
+        }
+        let mut chain = vec![];
+        let mut category = to;
+        while category != from {
+            let m = via
+                .get(category)
+                .ok_or_else(|| format!("There is no way from {} to {}", from, to))?;
+            chain.push(*m);
+            category = &m.source;
+        }
+        chain.reverse();
+        Ok(chain)
+    }
 
-    let ans2 = seeds1
-        .chunks(2)
-        .map(|nums| {
-            mappings
-                .iter()
-                .fold(
-                    vec![Range {
-                        start: nums[0],
-                        end: nums[0] + nums[1],
-                    }],
-                    |acc, v| v.map_ranges(acc),
-                )
+    /// Checks every mapping can be reached from seeds, locations can be reached at all, no
+    /// category maps back onto itself and no category is mapped onto by two maps, which would give
+    /// two ways of getting there that needn't agree
+    pub fn validate_chain(&self) -> Result<(), String> {
+        let mut indegree: HashMap<&str, usize> = HashMap::new();
+        for m in self.mappings.iter() {
+            indegree.entry(&m.source).or_default();
+            *indegree.entry(&m.destination).or_default() += 1;
+        }
+        let mut ready: Vec<&str> = indegree
+            .iter()
+            .filter(|(_, &n)| n == 0)
+            .map(|(&c, _)| c)
+            .collect();
+        while let Some(category) = ready.pop() {
+            indegree.remove(category);
+            for m in self.edges(category) {
+                let n = indegree.get_mut(m.destination.as_str()).unwrap();
+                *n -= 1;
+                if *n == 0 {
+                    ready.push(&m.destination);
+                }
+            }
+        }
+        // What is left is on a cycle or downstream of one, peel off the downstream part
+        let mut left: HashSet<&str> = indegree.into_keys().collect();
+        while let Some(&end) = left.iter().find(|&&c| {
+            self.edges(c)
+                .all(|m| !left.contains(m.destination.as_str()))
+        }) {
+            left.remove(end);
+        }
+        if !left.is_empty() {
+            let mut cycle: Vec<&str> = left.into_iter().collect();
+            cycle.sort();
+            return Err(format!("Categories {} map in a cycle", cycle.join(", ")));
+        }
+        for (i, m) in self.mappings.iter().enumerate() {
+            if let Some(other) = self.mappings[..i]
                 .iter()
-                .map(|r| r.start)
-                .min()
-                .unwrap()
-        })
-        .min()
-        .unwrap();
+                .find(|other| other.destination == m.destination)
+            {
+                return Err(format!(
+                    "Both the {} and the {} map lead to {}",
+                    other.name(),
+                    m.name(),
+                    m.destination
+                ));
+            }
+        }
+        let reachable: HashSet<&str> = self
+            .mappings
+            .iter()
+            .filter(|m| self.chain("seed", &m.destination).is_ok())
+            .map(|m| m.destination.as_str())
+            .chain(["seed"])
+            .collect();
+        if let Some(m) = self
+            .mappings
+            .iter()
+            .find(|m| !reachable.contains(m.source.as_str()))
+        {
+            return Err(format!("The {} map is not connected to seeds", m.name()));
+        }
+        self.chain("seed", "location").map(|_| ())
+    }
+}
+
+pub struct Day5;
+
+impl crate::runner::Solution for Day5 {
+    type Options = ();
+    type Parsed<'a> = Almanac;
+
+    /// Seed problems are left to part 2, part 1 reads the seeds one by one
+    fn parse<'a>(input: &'a str, _: &Self::Options) -> Result<Self::Parsed<'a>, String> {
+        let diagnostics: Vec<String> = validate_maps(input).iter().map(|d| d.to_string()).collect();
+        if !diagnostics.is_empty() {
+            return Err(diagnostics.join("\n"));
+        }
+        let (rest, seeds) =
+            parse_seeds(input).map_err(|e| format!("Could not read the seeds: {}", e))?;
+        let (_rest, mappings) =
+            parse_mapping(rest).map_err(|e| format!("Could not read the maps: {}", e))?;
+        let almanac = Almanac { seeds, mappings };
+        almanac.validate_chain()?;
+        Ok(almanac)
+    }
+
+    fn part1(almanac: &Self::Parsed<'_>) -> Result<String, String> {
+        let mappings = almanac.chain("seed", "location")?;
+        Ok(almanac
+            .seeds
+            .iter()
+            .map(|s| mappings.iter().fold(*s, |acc, v| v.map(acc)))
+            .min()
+            .unwrap()
+            .to_string())
+    }
+
+    fn part2(almanac: &Self::Parsed<'_>) -> Result<String, String> {
+        let mappings = almanac.chain("seed", "location")?;
+        Ok(compose(&mappings)
+            .min_over(&seed_ranges(&almanac.seeds)?)
+            .unwrap()
+            .to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::Solution;
+    use crate::utils::XorShift;
+
+    const EXAMPLE: &str = include_str!("inputs/day5.example.txt");
 
-    println!("part1: {}, part2: {}", ans1, ans2);
+    #[test]
+    fn example() {
+        let almanac = Day5::parse(EXAMPLE, &()).unwrap();
+        assert_eq!(Day5::part1(&almanac), Ok("35".into()));
+        assert_eq!(Day5::part2(&almanac), Ok("46".into()));
+        let mappings = almanac.chain("seed", "location").unwrap();
+        assert!(check(&almanac.seeds, &mappings, 1000, 10).is_ok());
+    }
+
+    #[test]
+    fn diagnostics() {
+        let input = "seeds: 79 14 55\n\nseed-to-soil map:\n50 98 2\n52 50 48\n60 60 5\nx 1 2\n\
+                     18446744073709551615 0 5\n";
+        let diagnostic = |line, message: &str| Diagnostic {
+            line,
+            message: message.into(),
+        };
+        assert_eq!(
+            validate(input),
+            [
+                diagnostic(
+                    1,
+                    "3 seeds can't all be paired into ranges, 55 has no length"
+                ),
+                diagnostic(6, "source 60..65 overlaps 50..98 from line 5"),
+                diagnostic(7, "expected destination, source and length, got 'x 1 2'"),
+                diagnostic(
+                    8,
+                    "destination 18446744073709551615 + length 5 overflows u64"
+                ),
+            ]
+        );
+        assert_eq!(
+            validate("seeds: 18446744073709551615 1 2 3"),
+            [diagnostic(
+                1,
+                "seed range 18446744073709551615 + 1 overflows u64"
+            )]
+        );
+        assert_eq!(
+            Day5::parse(input, &()).err(),
+            Some(
+                "line 6: source 60..65 overlaps 50..98 from line 5\n\
+                 line 7: expected destination, source and length, got 'x 1 2'\n\
+                 line 8: destination 18446744073709551615 + length 5 overflows u64"
+                    .into()
+            )
+        );
+        assert_eq!(validate(EXAMPLE), []);
+    }
+
+    #[test]
+    fn odd_seeds_only_stop_part2() {
+        let input = EXAMPLE.replacen("seeds: 79 14 55 13", "seeds: 79 14 55", 1);
+        let almanac = Day5::parse(&input, &()).unwrap();
+        assert_eq!(Day5::part1(&almanac), Ok("43".into()));
+        assert_eq!(
+            Day5::part2(&almanac),
+            Err("3 seeds can't all be paired into ranges, 55 has no length".into())
+        );
+    }
+
+    /// Maps between the categories of each `source-destination` pair, all shifting nothing
+    fn graph(edges: &[&str]) -> Almanac {
+        let mappings = edges
+            .iter()
+            .map(|edge| {
+                let (source, destination) = edge.split_once('-').unwrap();
+                Mapping {
+                    source: source.into(),
+                    destination: destination.into(),
+                    map: IntervalMap::default(),
+                }
+            })
+            .collect();
+        Almanac {
+            seeds: vec![],
+            mappings,
+        }
+    }
+
+    #[test]
+    fn chains() {
+        let almanac = Day5::parse(EXAMPLE, &()).unwrap();
+        let chain = almanac.chain("soil", "humidity").unwrap();
+        let names: Vec<String> = chain.iter().map(|m| m.name()).collect();
+        assert_eq!(
+            names,
+            [
+                "soil-to-fertilizer",
+                "fertilizer-to-water",
+                "water-to-light",
+                "light-to-temperature",
+                "temperature-to-humidity"
+            ]
+        );
+        // Seed 79 is planted in soil 81 and ends up at humidity 78
+        assert_eq!(compose(&chain).get(81), 78);
+        assert!(almanac.chain("seed", "seed").unwrap().is_empty());
+        assert_eq!(
+            almanac.chain("humidity", "soil").err(),
+            Some("There is no way from humidity to soil".into())
+        );
+    }
+
+    #[test]
+    fn chain_errors() {
+        let cases = [
+            (&["seed-soil", "soil-location"][..], Ok(())),
+            (
+                &["seed-soil", "soil-water", "water-soil", "water-location"],
+                Err("Categories soil, water map in a cycle"),
+            ),
+            (
+                &["seed-location", "fertilizer-water"],
+                Err("The fertilizer-to-water map is not connected to seeds"),
+            ),
+            (&["seed-soil"], Err("There is no way from seed to location")),
+            (
+                &["seed-soil", "soil-location", "seed-location"],
+                Err("Both the soil-to-location and the seed-to-location map lead to location"),
+            ),
+            (
+                &[
+                    "seed-soil",
+                    "seed-water",
+                    "soil-light",
+                    "water-light",
+                    "light-location",
+                ],
+                Err("Both the soil-to-light and the water-to-light map lead to light"),
+            ),
+        ];
+        for (edges, expected) in cases {
+            assert_eq!(
+                graph(edges).validate_chain(),
+                expected.map_err(String::from),
+                "{:?}",
+                edges
+            );
+        }
+    }
+
+    /// Up to eight shifted ranges below 200, overlapping ones are left out
+    fn random_map(rng: &mut XorShift) -> IntervalMap {
+        let mut map = IntervalMap::default();
+        for _ in 0..rng.within(&(0..9)) {
+            let start = rng.within(&(0..200));
+            let len = rng.within(&(1..40));
+            let _ = map.insert(start..start + len, rng.within(&(0..200)));
+        }
+        map
+    }
+
+    fn mapping(map: IntervalMap) -> Mapping {
+        Mapping {
+            source: "a".into(),
+            destination: "b".into(),
+            map,
+        }
+    }
+
+    #[test]
+    fn map_range_matches_each_seed() {
+        let mut rng = XorShift::default();
+        for _ in 0..500 {
+            let map = random_map(&mut rng);
+            let start = rng.within(&(0..250));
+            let seeds = start..start + rng.within(&(0..60));
+            let mut expected: Vec<u64> = seeds.clone().map(|s| map.get(s)).collect();
+            expected.sort_unstable();
+            let mut mapped: Vec<u64> = map.map_range(seeds.clone()).into_iter().flatten().collect();
+            mapped.sort_unstable();
+            assert_eq!(mapped, expected, "{:?} through\n{}", seeds, map);
+            assert_eq!(
+                map.min_over(std::slice::from_ref(&seeds)),
+                expected.first().copied(),
+                "{:?} through\n{}",
+                seeds,
+                map
+            );
+            expected.dedup();
+            let merged: Vec<u64> = map
+                .map_ranges(std::slice::from_ref(&seeds))
+                .into_iter()
+                .flatten()
+                .collect();
+            assert_eq!(merged, expected, "{:?} through\n{}", seeds, map);
+        }
+    }
+
+    #[test]
+    fn check_random_chains() {
+        let mut rng = XorShift::default();
+        for _ in 0..100 {
+            let mappings: Vec<Mapping> = (0..4).map(|_| mapping(random_map(&mut rng))).collect();
+            let mappings: Vec<&Mapping> = mappings.iter().collect();
+            let seeds: Vec<u64> = (0..6)
+                .flat_map(|_| [rng.within(&(0..250)), rng.within(&(1..30))])
+                .collect();
+            // Exhaustively and from the boundaries and samples alone
+            for exhaustive in [u64::MAX, 0] {
+                let checked = check(&seeds, &mappings, exhaustive, 5);
+                assert!(checked.is_ok(), "{:?}", checked);
+            }
+            let expected = seed_ranges(&seeds)
+                .unwrap()
+                .into_iter()
+                .flatten()
+                .map(|s| mappings.iter().fold(s, |acc, m| m.map(acc)))
+                .min();
+            assert_eq!(
+                compose(&mappings).min_over(&seed_ranges(&seeds).unwrap()),
+                expected
+            );
+        }
+    }
 }
@@ -1,77 +1,210 @@
-use std::{
-    iter::{once, zip},
-    ops::Range,
-};
+use std::{iter::zip, ops::Range};
 
 use nom::{
     bytes::complete::tag,
-    character::complete::{digit1, line_ending, space1, u64},
+    character::complete::{digit1, line_ending, space1},
     combinator::map,
     multi::many1,
     sequence::{preceded, separated_pair},
     IResult,
 };
+use num::{BigUint, ToPrimitive};
 
 #[derive(Debug)]
-struct Race {
-    time: u64,
-    distance: u64,
+pub struct Race {
+    pub time: u64,
+    pub distance: u64,
 }
 impl Race {
-    fn go(&self, holding_time: u64) -> u64 {
-        (self.time - holding_time) * holding_time
+    fn go(&self, holding_time: u64) -> Option<u64> {
+        (self.time - holding_time).checked_mul(holding_time)
     }
     fn won(&self, holding_time: u64) -> bool {
-        self.go(holding_time) > self.distance
+        // Going further than fits in a u64 beats any record that does
+        self.go(holding_time).is_none_or(|d| d > self.distance)
+    }
+    /// The holding times beating the record, between the roots of `h * (time - h) = distance`.
+    /// The integer square root puts the bounds within one of the roots and `won` settles them
+    /// exactly
+    pub fn winning(&self) -> Range<u64> {
+        let (time, distance) = (self.time as u128, self.distance as u128);
+        let discriminant = match (time * time).checked_sub(4 * distance) {
+            Some(d) if d > 0 => d,
+            _ => return 0..0,
+        };
+        let root = discriminant.isqrt() as u64;
+        let mut low = (self.time - root) / 2;
+        while low <= self.time / 2 && !self.won(low) {
+            low += 1;
+        }
+        while low > 0 && self.won(low - 1) {
+            low -= 1;
+        }
+        if low > self.time / 2 {
+            return 0..0;
+        }
+        // The wins are symmetric around time / 2
+        low..self.time - low + 1
     }
 }
 
-fn parse_races_part1(input: &str) -> IResult<&str, Vec<Race>> {
+/// A race of any length, solved as a `Race` whenever both numbers fit in a u64
+#[derive(Debug, Clone, PartialEq)]
+pub struct BigRace {
+    pub time: BigUint,
+    pub distance: BigUint,
+}
+
+impl From<&Race> for BigRace {
+    fn from(race: &Race) -> Self {
+        BigRace {
+            time: race.time.into(),
+            distance: race.distance.into(),
+        }
+    }
+}
+
+impl BigRace {
+    fn small(&self) -> Option<Race> {
+        Some(Race {
+            time: self.time.to_u64()?,
+            distance: self.distance.to_u64()?,
+        })
+    }
+    fn won(&self, holding_time: &BigUint) -> bool {
+        (&self.time - holding_time) * holding_time > self.distance
+    }
+    pub fn winning(&self) -> Range<BigUint> {
+        match self.small() {
+            Some(race) => {
+                let winning = race.winning();
+                winning.start.into()..winning.end.into()
+            }
+            None => self.winning_big(),
+        }
+    }
+    /// `Race::winning` on big integers
+    fn winning_big(&self) -> Range<BigUint> {
+        let (zero, one) = (BigUint::from(0_u32), BigUint::from(1_u32));
+        let square = &self.time * &self.time;
+        let four_distance = &self.distance * 4_u32;
+        if square <= four_distance {
+            return zero.clone()..zero;
+        }
+        let root = (square - four_distance).sqrt();
+        let half = &self.time / 2_u32;
+        let mut low = (&self.time - root) / 2_u32;
+        while low <= half && !self.won(&low) {
+            low += &one;
+        }
+        while low > zero && self.won(&(&low - &one)) {
+            low -= &one;
+        }
+        if low > half {
+            return zero.clone()..zero;
+        }
+        let high = &self.time - &low + one;
+        low..high
+    }
+    pub fn ways(&self) -> BigUint {
+        let winning = self.winning();
+        winning.end - winning.start
+    }
+}
+
+fn numbers_parser<'a>(
+    label: &'static str,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<&'a str>> {
+    preceded(tag(label), many1(preceded(space1, digit1)))
+}
+
+fn number(digits: &str) -> BigUint {
+    digits.parse().unwrap()
+}
+
+fn parse_races_part1(input: &str) -> IResult<&str, Vec<BigRace>> {
     map(
         separated_pair(
-            preceded(tag("Time:"), many1(preceded(space1, u64))),
+            numbers_parser("Time:"),
             line_ending,
-            preceded(tag("Distance:"), many1(preceded(space1, u64))),
+            numbers_parser("Distance:"),
         ),
         |(t, d)| {
             zip(t, d)
-                .map(|(time, distance)| Race { time, distance })
+                .map(|(time, distance)| BigRace {
+                    time: number(time),
+                    distance: number(distance),
+                })
                 .collect()
         },
     )(input)
 }
-fn parse_race_part2(input: &str) -> IResult<&str, Race> {
+fn parse_race_part2(input: &str) -> IResult<&str, BigRace> {
     map(
         separated_pair(
-            preceded(tag("Time:"), many1(preceded(space1, digit1))),
+            numbers_parser("Time:"),
             line_ending,
-            preceded(tag("Distance:"), many1(preceded(space1, digit1))),
+            numbers_parser("Distance:"),
         ),
-        |(t, d)| Race {
-            time: u64::from_str_radix(&t.join(""), 10).unwrap(),
-            distance: u64::from_str_radix(&d.join(""), 10).unwrap(),
+        |(t, d)| BigRace {
+            time: number(&t.join("")),
+            distance: number(&d.join("")),
         },
     )(input)
 }
 
-pub fn main() {
-    let input = include_str!("inputs/day6.txt");
-    let (_rest, races) = parse_races_part1(input).unwrap();
-    let ans1 = races.iter().fold(1usize, |acc, e| {
-        Range {
-            start: 0,
-            end: e.time,
+pub struct Day6;
+
+impl crate::runner::Solution for Day6 {
+    type Options = ();
+    type Parsed<'a> = (Vec<BigRace>, BigRace);
+
+    fn parse<'a>(input: &'a str, _: &Self::Options) -> Result<Self::Parsed<'a>, String> {
+        let (_rest, races) = parse_races_part1(input).unwrap();
+        let (_rest, race) = parse_race_part2(input).unwrap();
+        Ok((races, race))
+    }
+
+    fn part1((races, _): &Self::Parsed<'_>) -> Result<String, String> {
+        Ok(races
+            .iter()
+            .map(|race| race.ways())
+            .product::<BigUint>()
+            .to_string())
+    }
+
+    fn part2((_, race): &Self::Parsed<'_>) -> Result<String, String> {
+        Ok(race.ways().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tries every holding time on pseudo random races, the big integer solver is held to the same
+    /// answers
+    #[test]
+    fn winning_matches_every_holding_time() {
+        let mut rng = crate::utils::XorShift::default();
+        for _ in 0..2000 {
+            let time = rng.within(&(0..1001));
+            let best = (time / 2) * (time - time / 2);
+            // Mostly records near the best distance, where rounding goes wrong
+            let distance = best.saturating_sub(rng.within(&(0..time + 1))) + rng.within(&(0..2));
+            let race = Race { time, distance };
+            let winning: Vec<u64> = (0..=time).filter(|&h| race.won(h)).collect();
+            let expected = match (winning.first(), winning.last()) {
+                (Some(&low), Some(&high)) => low..high + 1,
+                _ => 0..0,
+            };
+            assert_eq!(race.winning(), expected, "{:?}", race);
+            assert_eq!(
+                BigRace::from(&race).winning_big(),
+                expected.start.into()..expected.end.into(),
+                "{:?}",
+                race
+            );
         }
-        .filter(|t| e.won(*t))
-        .count()
-            * acc
-    });
-    let (_rest, race) = parse_race_part2(input).unwrap();
-    let ans2 = Range {
-        start: 0,
-        end: race.time,
     }
-    .filter(|t| race.won(*t))
-    .count();
-    println!("part1: {}, part2: {}", ans1, ans2);
 }
@@ -9,7 +9,8 @@ use nom::{
     IResult,
 };
 
-struct Hand {
+#[derive(Clone)]
+pub struct Hand {
     cards: [u8; 5],
     bid: u64,
 }
@@ -26,25 +27,21 @@ impl fmt::Debug for Hand {
 
 impl Hand {
     fn five(&self) -> bool {
-        self.cards
-            .iter()
-            .fold(false, |a, c| self.num_j(c) == 5 || a)
+        self.cards.iter().any(|c| self.num_j(c) == 5)
     }
     fn four(&self) -> bool {
-        self.cards
-            .iter()
-            .fold(false, |a, c| self.num_j(c) == 4 || a)
+        self.cards.iter().any(|c| self.num_j(c) == 4)
     }
     fn full_house(&self) -> bool {
         let p1 = self
             .cards
             .iter()
-            .fold((false, '0' as u8), |acc, c| match self.num_j(c) == 3 {
+            .fold((false, b'0'), |acc, c| match self.num_j(c) == 3 {
                 true => (true, *c),
                 _ => acc,
             });
-        let p2 = self.cards.iter().fold((false, '0' as u8), |acc, c| {
-            match self.num(c) == 2 && *c != p1.1 && *c != 'J' as u8 {
+        let p2 = self.cards.iter().fold((false, b'0'), |acc, c| {
+            match self.num(c) == 2 && *c != p1.1 && *c != b'J' {
                 true => (true, *c),
                 false => acc,
             }
@@ -52,19 +49,17 @@ impl Hand {
         p1.0 && p2.0
     }
     fn three(&self) -> bool {
-        self.cards
-            .iter()
-            .fold(false, |a, c| self.num_j(c) == 3 || a)
+        self.cards.iter().any(|c| self.num_j(c) == 3)
     }
     fn two_pair(&self) -> bool {
         let p1 = self
             .cards
             .iter()
-            .fold((false, '0' as u8), |acc, c| match self.num_j(c) == 2 {
+            .fold((false, b'0'), |acc, c| match self.num_j(c) == 2 {
                 true => (true, *c),
                 _ => acc,
             });
-        let p2 = self.cards.iter().fold((false, '0' as u8), |acc, c| {
+        let p2 = self.cards.iter().fold((false, b'0'), |acc, c| {
             match self.num(c) == 2 && *c != p1.1 && *c != 1 {
                 true => (true, *c),
                 false => acc,
@@ -73,9 +68,7 @@ impl Hand {
         p1.0 && p2.0
     }
     fn pair(&self) -> bool {
-        self.cards
-            .iter()
-            .fold(false, |a, c| self.num_j(c) == 2 || a)
+        self.cards.iter().any(|c| self.num_j(c) == 2)
     }
     fn num(&self, check_num: &u8) -> usize {
         self.cards
@@ -118,30 +111,26 @@ impl Eq for Hand {}
 
 impl PartialOrd for Hand {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Hand {
+    fn cmp(&self, other: &Self) -> Ordering {
         if let Some(res) = self.compare_type(other, Hand::five) {
-            Some(res)
+            res
         } else if let Some(res) = self.compare_type(other, Hand::four) {
-            Some(res)
+            res
         } else if let Some(res) = self.compare_type(other, Hand::full_house) {
-            Some(res)
+            res
         } else if let Some(res) = self.compare_type(other, Hand::three) {
-            Some(res)
+            res
         } else if let Some(res) = self.compare_type(other, Hand::two_pair) {
-            Some(res)
+            res
         } else if let Some(res) = self.compare_type(other, Hand::pair) {
-            Some(res)
-        } else {
-            Some(self.tie_breaker(other))
-        }
-    }
-}
-
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        if let Some(res) = self.partial_cmp(other) {
             res
         } else {
-            panic!("must be comparable")
+            self.tie_breaker(other)
         }
     }
 }
@@ -151,7 +140,7 @@ fn parse_hand(input: &str) -> IResult<&str, Hand> {
         separated_pair(
             count(
                 map(anychar, |d| match d {
-                    '2'..='9' => d as u8 - '0' as u8,
+                    '2'..='9' => d as u8 - b'0',
                     'T' => 10,
                     'J' => 1,
                     'Q' => 12,
@@ -175,13 +164,37 @@ fn parse_hands(input: &str) -> IResult<&str, Vec<Hand>> {
     separated_list1(line_ending, parse_hand)(input)
 }
 
-pub fn main() {
-    let input = include_str!("inputs/day7.txt");
-    let (_rest, mut hands) = parse_hands(input).unwrap();
+fn winnings(mut hands: Vec<Hand>) -> usize {
     hands.sort();
-    let ans = hands
+    hands
         .iter()
         .enumerate()
-        .fold(0, |acc, (i, e)| acc + (i + 1) * e.bid as usize);
-    println!("ans: {}", ans);
+        .fold(0, |acc, (i, e)| acc + (i + 1) * e.bid as usize)
+}
+
+pub struct Day7;
+
+impl crate::runner::Solution for Day7 {
+    type Options = ();
+    type Parsed<'a> = Vec<Hand>;
+
+    fn parse<'a>(input: &'a str, _: &Self::Options) -> Result<Self::Parsed<'a>, String> {
+        Ok(parse_hands(input).unwrap().1)
+    }
+
+    fn part1(hands: &Self::Parsed<'_>) -> Result<String, String> {
+        // Without jokers a J is just the card between T and Q
+        let hands = hands
+            .iter()
+            .map(|h| Hand {
+                cards: h.cards.map(|c| if c == 1 { 11 } else { c }),
+                bid: h.bid,
+            })
+            .collect();
+        Ok(winnings(hands).to_string())
+    }
+
+    fn part2(hands: &Self::Parsed<'_>) -> Result<String, String> {
+        Ok(winnings(hands.clone()).to_string())
+    }
 }
@@ -21,7 +21,9 @@ fn parse_map_line(input: &str) -> IResult<&str, (&str, (&str, &str))> {
     )(input)
 }
 
-fn parse_map(input: &str) -> IResult<&str, HashMap<&str, (&str, &str)>> {
+type Network<'a> = HashMap<&'a str, (&'a str, &'a str)>;
+
+fn parse_map(input: &str) -> IResult<&str, Network<'_>> {
     fold_many1(parse_map_line, HashMap::new, |mut map, (key, value)| {
         map.insert(key, value);
         map
@@ -29,7 +31,7 @@ fn parse_map(input: &str) -> IResult<&str, HashMap<&str, (&str, &str)>> {
 }
 
 #[derive(Debug)]
-enum Dir {
+pub enum Dir {
     Left,
     Right,
 }
@@ -49,50 +51,62 @@ fn parse_instructions(input: &str) -> IResult<&str, Vec<Dir>> {
     })(input)
 }
 
-fn parse_puzzle(input: &str) -> IResult<&str, (Vec<Dir>, HashMap<&str, (&str, &str)>)> {
+fn parse_puzzle(input: &str) -> IResult<&str, (Vec<Dir>, Network<'_>)> {
     separated_pair(parse_instructions, multispace1, parse_map)(input)
 }
 
-pub fn main() {
-    let input = include_str!("inputs/day8.txt");
-    let (_rest, (dirs, map)) = parse_puzzle(input).unwrap();
-    let mut ans1 = 0;
-    let mut cur = "AAA";
-    for d in dirs.iter().cycle() {
-        cur = match d {
-            Dir::Left => map.get(&cur).unwrap().0,
-            Dir::Right => map.get(&cur).unwrap().1,
-        };
-        ans1 += 1;
-        if cur == "ZZZ" {
-            break;
-        }
-    }
+pub struct Day8;
 
-    let starting_points: Vec<&&str> = map
-        .keys()
-        .filter(|k| k.chars().nth(2) == Some('A'))
-        .collect();
-    let mut periods = vec![];
-    for sp in starting_points {
-        let mut cur = *sp;
-        let mut steps = 0;
+impl crate::runner::Solution for Day8 {
+    type Options = ();
+    type Parsed<'a> = (Vec<Dir>, Network<'a>);
 
+    fn parse<'a>(input: &'a str, _: &Self::Options) -> Result<Self::Parsed<'a>, String> {
+        Ok(parse_puzzle(input).unwrap().1)
+    }
+
+    fn part1((dirs, map): &Self::Parsed<'_>) -> Result<String, String> {
+        let mut ans1 = 0;
+        let mut cur = "AAA";
         for d in dirs.iter().cycle() {
             cur = match d {
                 Dir::Left => map.get(&cur).unwrap().0,
                 Dir::Right => map.get(&cur).unwrap().1,
             };
-            steps += 1;
-            if cur.chars().nth(2).unwrap() == 'Z' {
-                periods.push(steps as u64);
+            ans1 += 1;
+            if cur == "ZZZ" {
                 break;
             }
         }
+        Ok(ans1.to_string())
+    }
+
+    fn part2((dirs, map): &Self::Parsed<'_>) -> Result<String, String> {
+        let starting_points: Vec<&&str> = map
+            .keys()
+            .filter(|k| k.chars().nth(2) == Some('A'))
+            .collect();
+        let mut periods = vec![];
+        for sp in starting_points {
+            let mut cur = *sp;
+            let mut steps = 0;
+
+            for d in dirs.iter().cycle() {
+                cur = match d {
+                    Dir::Left => map.get(&cur).unwrap().0,
+                    Dir::Right => map.get(&cur).unwrap().1,
+                };
+                steps += 1;
+                if cur.chars().nth(2).unwrap() == 'Z' {
+                    periods.push(steps as u64);
+                    break;
+                }
+            }
+        }
+        Ok(periods
+            .into_iter()
+            .reduce(num::integer::lcm)
+            .unwrap()
+            .to_string())
     }
-    let ans2 = periods
-        .into_iter()
-        .reduce(|acc, x| num::integer::lcm(acc, x))
-        .unwrap();
-    println!("ans1: {}, ans2: {}", ans1, ans2);
 }
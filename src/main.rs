@@ -1,9 +1,25 @@
 #[macro_use]
 extern crate lazy_static;
 
+mod cli;
+mod day1;
+mod day10;
 mod day11;
+mod day2;
+mod day3;
+mod day4;
+mod day5;
+mod day6;
+mod day7;
+mod day8;
+mod day9;
+mod profile;
+mod runner;
+mod scaffold;
+mod submit;
 mod utils;
+mod watch;
 
 fn main() {
-    day11::main()
+    cli::main()
 }
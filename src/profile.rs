@@ -0,0 +1,80 @@
+#[cfg_attr(not(feature = "alloc-profile"), allow(dead_code))]
+#[derive(Debug, Clone, Copy)]
+pub struct Usage {
+    pub count: usize,
+    pub peak: usize,
+}
+
+#[cfg(feature = "alloc-profile")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    // Counted per thread, so days solved in parallel don't see each others allocations
+    thread_local! {
+        static CURRENT: Cell<usize> = const { Cell::new(0) };
+        static PEAK: Cell<usize> = const { Cell::new(0) };
+        static COUNT: Cell<usize> = const { Cell::new(0) };
+    }
+
+    pub struct Counting;
+
+    fn grow(size: usize) {
+        let current = CURRENT.get() + size;
+        CURRENT.set(current);
+        PEAK.set(PEAK.get().max(current));
+    }
+
+    fn shrink(size: usize) {
+        CURRENT.set(CURRENT.get().saturating_sub(size));
+    }
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            COUNT.set(COUNT.get() + 1);
+            grow(layout.size());
+            System.alloc(layout)
+        }
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            shrink(layout.size());
+            System.dealloc(ptr, layout)
+        }
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            COUNT.set(COUNT.get() + 1);
+            shrink(layout.size());
+            grow(new_size);
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: Counting = Counting;
+
+    pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<super::Usage>) {
+        let base = CURRENT.get();
+        PEAK.set(base);
+        COUNT.set(0);
+        let res = f();
+        let usage = super::Usage {
+            count: COUNT.get(),
+            peak: PEAK.get() - base,
+        };
+        (res, Some(usage))
+    }
+}
+
+#[cfg(feature = "alloc-profile")]
+pub use counting::measure;
+
+#[cfg(not(feature = "alloc-profile"))]
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<Usage>) {
+    (f(), None)
+}
+
+pub fn bytes(n: usize) -> String {
+    match n {
+        n if n >= 1 << 20 => format!("{:.1}MiB", n as f64 / (1 << 20) as f64),
+        n if n >= 1 << 10 => format!("{:.1}KiB", n as f64 / (1 << 10) as f64),
+        n => format!("{}B", n),
+    }
+}
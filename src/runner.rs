@@ -0,0 +1,210 @@
+use std::{
+    any::Any,
+    fs, panic,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::cli::Args;
+use crate::profile::Usage;
+
+pub trait Solution {
+    /// Settings taken from the command line, the puzzle's own rules by default
+    type Options: Default;
+    type Parsed<'a>;
+    fn options(_args: &Args) -> Result<Self::Options, String> {
+        Ok(Self::Options::default())
+    }
+    /// Fails on input neither part can be solved from
+    fn parse<'a>(input: &'a str, options: &Self::Options) -> Result<Self::Parsed<'a>, String>;
+    fn part1(parsed: &Self::Parsed<'_>) -> Result<String, String>;
+    fn part2(parsed: &Self::Parsed<'_>) -> Result<String, String>;
+}
+
+pub struct Day {
+    pub day: u32,
+    pub solve: fn(&str, &Args) -> Result<Solved, String>,
+}
+
+pub struct Timings {
+    pub parse: Duration,
+    pub part1: Duration,
+    pub part2: Duration,
+}
+
+pub struct Allocations {
+    pub parse: Usage,
+    pub part1: Usage,
+    pub part2: Usage,
+}
+
+/// A part that can't be solved on this input leaves its error in place of the answer
+pub struct Solved {
+    pub answers: [Result<String, String>; 2],
+    pub timings: Timings,
+    pub allocations: Option<Allocations>,
+}
+
+pub struct Report {
+    pub day: u32,
+    pub result: Result<Solved, String>,
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration, Option<Usage>) {
+    let start = Instant::now();
+    let (res, usage) = crate::profile::measure(f);
+    (res, start.elapsed(), usage)
+}
+
+fn solve<S: Solution>(input: &str, args: &Args) -> Result<Solved, String> {
+    let options = S::options(args)?;
+    let (parsed, parse, parse_usage) = timed(|| S::parse(input, &options));
+    let parsed = parsed?;
+    let (ans1, part1, part1_usage) = timed(|| S::part1(&parsed));
+    let (ans2, part2, part2_usage) = timed(|| S::part2(&parsed));
+    Ok(Solved {
+        answers: [ans1, ans2],
+        timings: Timings {
+            parse,
+            part1,
+            part2,
+        },
+        allocations: parse_usage.zip(part1_usage).zip(part2_usage).map(
+            |((parse, part1), part2)| Allocations {
+                parse,
+                part1,
+                part2,
+            },
+        ),
+    })
+}
+
+pub const DAYS: &[Day] = &[
+    Day {
+        day: 1,
+        solve: solve::<crate::day1::Day1>,
+    },
+    Day {
+        day: 2,
+        solve: solve::<crate::day2::Day2>,
+    },
+    Day {
+        day: 3,
+        solve: solve::<crate::day3::Day3>,
+    },
+    Day {
+        day: 4,
+        solve: solve::<crate::day4::Day4>,
+    },
+    Day {
+        day: 5,
+        solve: solve::<crate::day5::Day5>,
+    },
+    Day {
+        day: 6,
+        solve: solve::<crate::day6::Day6>,
+    },
+    Day {
+        day: 7,
+        solve: solve::<crate::day7::Day7>,
+    },
+    Day {
+        day: 8,
+        solve: solve::<crate::day8::Day8>,
+    },
+    Day {
+        day: 9,
+        solve: solve::<crate::day9::Day9>,
+    },
+    Day {
+        day: 10,
+        solve: solve::<crate::day10::Day10>,
+    },
+    Day {
+        day: 11,
+        solve: solve::<crate::day11::Day11>,
+    },
+];
+
+pub fn input_path(day: u32) -> String {
+    format!("src/inputs/day{}.txt", day)
+}
+
+pub fn example_path(day: u32) -> String {
+    format!("src/inputs/day{}.example.txt", day)
+}
+
+pub fn find(day: u32) -> Result<&'static Day, String> {
+    DAYS.iter()
+        .find(|d| d.day == day)
+        .ok_or_else(|| format!("Day {} is not implemented", day))
+}
+
+/// The puzzle input, or the example with `--example`
+pub fn read_input(day: u32) -> Result<String, String> {
+    let path = match crate::cli::args().flag("--example") {
+        true => example_path(day),
+        false => input_path(day),
+    };
+    fs::read_to_string(&path).map_err(|e| format!("Could not read {}: {}", path, e))
+}
+
+/// Fails when the day isn't implemented, its input can't be read or the day's options on the
+/// command line are invalid
+pub fn run(day: u32) -> Result<Solved, String> {
+    (find(day)?.solve)(&read_input(day)?, crate::cli::args())
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match (
+        payload.downcast_ref::<&str>(),
+        payload.downcast_ref::<String>(),
+    ) {
+        (Some(msg), _) => msg.to_string(),
+        (_, Some(msg)) => msg.clone(),
+        _ => "panicked".into(),
+    }
+}
+
+/// Solves every day on `jobs` worker threads, a panicking day is reported instead of aborting the rest
+pub fn run_all(jobs: usize) -> Vec<Report> {
+    let next = AtomicUsize::new(0);
+    let reports = Mutex::new(vec![]);
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    thread::scope(|s| {
+        for _ in 0..jobs {
+            s.spawn(|| {
+                while let Some(day) = DAYS.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let result = panic::catch_unwind(|| run(day.day))
+                        .map_err(panic_message)
+                        .and_then(|solved| solved);
+                    reports.lock().unwrap().push(Report {
+                        day: day.day,
+                        result,
+                    });
+                }
+            });
+        }
+    });
+    panic::set_hook(hook);
+    let mut reports = reports.into_inner().unwrap();
+    reports.sort_by_key(|r| r.day);
+    reports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_days() {
+        assert_eq!(find(3).map(|d| d.day), Ok(3));
+        assert_eq!(find(99).err(), Some("Day 99 is not implemented".into()));
+        assert_eq!(run(99).err(), Some("Day 99 is not implemented".into()));
+    }
+}
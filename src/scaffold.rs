@@ -0,0 +1,191 @@
+use std::{fs, path::Path};
+
+const MAIN: &str = "src/main.rs";
+const RUNNER: &str = "src/runner.rs";
+const DAYS_START: &str = "pub const DAYS: &[Day] = &[";
+
+const TEMPLATE: &str = r#"use nom::{
+    character::complete::{line_ending, not_line_ending},
+    multi::separated_list1,
+    IResult,
+};
+
+fn parse_puzzle(input: &str) -> IResult<&str, Vec<&str>> {
+    separated_list1(line_ending, not_line_ending)(input)
+}
+
+pub struct Day{N};
+
+impl crate::runner::Solution for Day{N} {
+    type Options = ();
+    type Parsed<'a> = Vec<&'a str>;
+
+    fn parse<'a>(input: &'a str, _: &Self::Options) -> Result<Self::Parsed<'a>, String> {
+        parse_puzzle(input.trim())
+            .map(|(_, lines)| lines)
+            .map_err(|e| e.to_string())
+    }
+
+    fn part1(_lines: &Self::Parsed<'_>) -> Result<String, String> {
+        todo!()
+    }
+
+    fn part2(_lines: &Self::Parsed<'_>) -> Result<String, String> {
+        todo!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Day{N};
+    use crate::runner::Solution;
+
+    const EXAMPLE: &str = include_str!("inputs/day{N}.example.txt");
+
+    #[test]
+    fn part1() {
+        assert_eq!(Day{N}::part1(&Day{N}::parse(EXAMPLE, &()).unwrap()), Ok("".into()));
+    }
+
+    #[test]
+    fn part2() {
+        assert_eq!(Day{N}::part2(&Day{N}::parse(EXAMPLE, &()).unwrap()), Ok("".into()));
+    }
+}
+"#;
+
+fn eol(content: &str) -> &'static str {
+    match content.contains("\r\n") {
+        true => "\r\n",
+        false => "\n",
+    }
+}
+
+fn read(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))
+}
+
+fn write(path: &str, content: &str) -> Result<(), String> {
+    fs::write(path, content).map_err(|e| format!("Could not write {}: {}", path, e))
+}
+
+/// Adds `mod dayN;` to the module list, keeping it sorted the way rustfmt does
+fn register_mod(main: &str, day: u32) -> String {
+    let eol = eol(main);
+    let mut lines: Vec<String> = main.lines().map(String::from).collect();
+    let first = lines.iter().position(|l| l.starts_with("mod ")).unwrap();
+    let last = lines.iter().rposition(|l| l.starts_with("mod ")).unwrap();
+    let mut mods = lines.split_off(first);
+    let rest = mods.split_off(last - first + 1);
+    mods.push(format!("mod day{};", day));
+    mods.sort_by_key(|m| m.trim_end_matches(';').to_string());
+    lines.extend(mods);
+    lines.extend(rest);
+    lines.join(eol) + eol
+}
+
+/// Appends the new day to the end of the `DAYS` table
+fn register_day(runner: &str, day: u32) -> String {
+    let eol = eol(runner);
+    let start = runner.find(DAYS_START).expect("runner must define DAYS");
+    let end = start + runner[start..].find(&format!("{}];", eol)).unwrap() + eol.len();
+    let entry = format!(
+        "    Day {{{eol}        day: {day},{eol}        solve: solve::<crate::day{day}::Day{day}>,{eol}    }},{eol}",
+        eol = eol,
+        day = day
+    );
+    format!("{}{}{}", &runner[..end], entry, &runner[end..])
+}
+
+pub fn new_day(day: u32) -> Result<(), String> {
+    if crate::runner::find(day).is_ok() {
+        return Err(format!("Day {} is already in {}", day, RUNNER));
+    }
+    let module = format!("src/day{}.rs", day);
+    if Path::new(&module).exists() {
+        return Err(format!("{} already exists", module));
+    }
+    let main = read(MAIN)?;
+    let runner = read(RUNNER)?;
+    let eol = eol(&main);
+
+    write(
+        &module,
+        &TEMPLATE.replace("{N}", &day.to_string()).replace('\n', eol),
+    )?;
+    write(MAIN, &register_mod(&main, day))?;
+    write(RUNNER, &register_day(&runner, day))?;
+    for path in [
+        crate::runner::input_path(day),
+        crate::runner::example_path(day),
+    ] {
+        if !Path::new(&path).exists() {
+            write(&path, "")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAIN_RS: &str = "use std::env;\n\nmod cli;\nmod day1;\nmod day10;\nmod day11;\nmod day2;\nmod utils;\n\nfn main() {}\n";
+
+    const RUNNER_RS: &str = "pub const DAYS: &[Day] = &[\n    Day {\n        day: 1,\n        solve: solve::<crate::day1::Day1>,\n    },\n];\n\npub fn input_path() {}\n";
+
+    fn mods(main: &str) -> Vec<&str> {
+        main.lines().filter(|l| l.starts_with("mod ")).collect()
+    }
+
+    #[test]
+    fn mods_sorted_like_rustfmt() {
+        let main = register_mod(MAIN_RS, 12);
+        assert_eq!(
+            mods(&main),
+            [
+                "mod cli;",
+                "mod day1;",
+                "mod day10;",
+                "mod day11;",
+                "mod day12;",
+                "mod day2;",
+                "mod utils;"
+            ]
+        );
+        assert!(main.starts_with("use std::env;\n\nmod cli;\n"));
+        assert!(main.ends_with("mod utils;\n\nfn main() {}\n"));
+        assert_eq!(mods(&register_mod(MAIN_RS, 3))[5], "mod day3;");
+    }
+
+    #[test]
+    fn days_appended_to_the_table() {
+        let runner = register_day(RUNNER_RS, 12);
+        assert_eq!(
+            runner,
+            RUNNER_RS.replace(
+                "    },\n];",
+                "    },\n    Day {\n        day: 12,\n        solve: solve::<crate::day12::Day12>,\n    },\n];"
+            )
+        );
+    }
+
+    #[test]
+    fn crlf_files_stay_crlf() {
+        let crlf = |s: &str| s.replace('\n', "\r\n");
+        let lone_lf = |s: &str| s.replace("\r\n", "").contains('\n');
+
+        let main = register_mod(&crlf(MAIN_RS), 12);
+        assert!(!lone_lf(&main));
+        assert_eq!(main, crlf(&register_mod(MAIN_RS, 12)));
+
+        let runner = register_day(&crlf(RUNNER_RS), 12);
+        assert!(!lone_lf(&runner));
+        assert_eq!(runner, crlf(&register_day(RUNNER_RS, 12)));
+    }
+
+    #[test]
+    fn registered_days_refused() {
+        assert_eq!(new_day(3), Err(format!("Day 3 is already in {}", RUNNER)));
+    }
+}
@@ -0,0 +1,366 @@
+use std::{
+    env, fmt, fs,
+    io::Write,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const HISTORY: &str = "submissions.txt";
+const SESSION: &str = ".session";
+const DEFAULT_ENDPOINT: &str = "https://adventofcode.com/2023/day/{day}/answer";
+// AoC makes you wait at least a minute after every wrong answer
+const COOLDOWN: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Correct,
+    Wrong,
+    TooHigh,
+    TooLow,
+    Wait(u64),
+    AlreadySolved,
+    Unknown,
+}
+
+impl Outcome {
+    fn from_response(body: &str) -> Outcome {
+        if body.contains("That's the right answer") {
+            Outcome::Correct
+        } else if body.contains("your answer is too high") {
+            Outcome::TooHigh
+        } else if body.contains("your answer is too low") {
+            Outcome::TooLow
+        } else if body.contains("That's not the right answer") {
+            Outcome::Wrong
+        } else if body.contains("You gave an answer too recently") {
+            Outcome::Wait(parse_wait(body).unwrap_or(COOLDOWN))
+        } else if body.contains("You don't seem to be solving the right level") {
+            Outcome::AlreadySolved
+        } else {
+            Outcome::Unknown
+        }
+    }
+    fn is_wrong(&self) -> bool {
+        matches!(self, Outcome::Wrong | Outcome::TooHigh | Outcome::TooLow)
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "correct"),
+            Outcome::Wrong => write!(f, "wrong"),
+            Outcome::TooHigh => write!(f, "too_high"),
+            Outcome::TooLow => write!(f, "too_low"),
+            Outcome::Wait(s) => write!(f, "wait:{}", s),
+            Outcome::AlreadySolved => write!(f, "already_solved"),
+            Outcome::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "correct" => Ok(Outcome::Correct),
+            "wrong" => Ok(Outcome::Wrong),
+            "too_high" => Ok(Outcome::TooHigh),
+            "too_low" => Ok(Outcome::TooLow),
+            "already_solved" => Ok(Outcome::AlreadySolved),
+            "unknown" => Ok(Outcome::Unknown),
+            s => match s.strip_prefix("wait:").map(u64::from_str) {
+                Some(Ok(secs)) => Ok(Outcome::Wait(secs)),
+                _ => Err(format!("Unknown outcome '{}'", s)),
+            },
+        }
+    }
+}
+
+// "You have 1m 23s left to wait."
+fn parse_wait(body: &str) -> Option<u64> {
+    let start = body.find("You have ")? + "You have ".len();
+    let end = start + body[start..].find(" left to wait")?;
+    body[start..end].split_whitespace().try_fold(0, |acc, t| {
+        let (n, unit) = t.split_at(t.len() - 1);
+        let n = n.parse::<u64>().ok()?;
+        match unit {
+            "m" => Some(acc + n * 60),
+            "s" => Some(acc + n),
+            _ => None,
+        }
+    })
+}
+
+#[derive(Debug)]
+struct Attempt {
+    time: u64,
+    day: u32,
+    part: u32,
+    answer: String,
+    outcome: Outcome,
+}
+
+impl fmt::Display for Attempt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}",
+            self.time, self.day, self.part, self.answer, self.outcome
+        )
+    }
+}
+
+impl FromStr for Attempt {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split('\t').collect();
+        if fields.len() != 5 {
+            return Err(format!("Malformed history line '{}'", s));
+        }
+        let num_err = |e: std::num::ParseIntError| format!("{} in '{}'", e, s);
+        Ok(Attempt {
+            time: fields[0].parse().map_err(num_err)?,
+            day: fields[1].parse().map_err(num_err)?,
+            part: fields[2].parse().map_err(num_err)?,
+            answer: fields[3].into(),
+            outcome: fields[4].parse()?,
+        })
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn read_history() -> Result<Vec<Attempt>, String> {
+    match fs::read_to_string(HISTORY) {
+        Ok(history) => history.lines().map(Attempt::from_str).collect(),
+        Err(_) => Ok(vec![]),
+    }
+}
+
+fn record(attempt: &Attempt) -> Result<(), String> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(HISTORY)
+        .map_err(|e| format!("Could not open {}: {}", HISTORY, e))?;
+    writeln!(file, "{}", attempt).map_err(|e| format!("Could not write {}: {}", HISTORY, e))
+}
+
+/// Refuses answers that are throttled, already known to be wrong or outside a learned bound
+fn check(history: &[Attempt], day: u32, part: u32, answer: &str, now: u64) -> Result<(), String> {
+    if let Some(last) = history.last() {
+        let wait_until = match last.outcome {
+            Outcome::Wait(secs) => last.time + secs,
+            o if o.is_wrong() => last.time + COOLDOWN,
+            _ => 0,
+        };
+        if now < wait_until {
+            return Err(format!("Throttled, wait another {}s", wait_until - now));
+        }
+    }
+    let attempts = history.iter().filter(|a| a.day == day && a.part == part);
+    let value = answer.parse::<i128>().ok();
+    for a in attempts {
+        match (a.outcome, value, a.answer.parse::<i128>().ok()) {
+            (Outcome::Correct | Outcome::AlreadySolved, _, _) => {
+                return Err(format!("Day {} part {} is already solved", day, part))
+            }
+            (o, _, _) if o.is_wrong() && a.answer == answer => {
+                return Err(format!("{} is already known to be {}", answer, o))
+            }
+            (Outcome::TooHigh, Some(v), Some(bound)) if v >= bound => {
+                return Err(format!(
+                    "{} is not below known too high answer {}",
+                    v, bound
+                ))
+            }
+            (Outcome::TooLow, Some(v), Some(bound)) if v <= bound => {
+                return Err(format!("{} is not above known too low answer {}", v, bound))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn session() -> Result<String, String> {
+    env::var("AOC_SESSION")
+        .or_else(|_| fs::read_to_string(SESSION).map(|s| s.trim().into()))
+        .map_err(|_| format!("Set AOC_SESSION or put the session cookie in {}", SESSION))
+}
+
+pub fn submit(
+    day: u32,
+    part: u32,
+    answer: &str,
+    endpoint: Option<&str>,
+) -> Result<Outcome, String> {
+    let history = read_history()?;
+    check(&history, day, part, answer, now())?;
+
+    let endpoint = endpoint
+        .map(String::from)
+        .or_else(|| env::var("AOC_ENDPOINT").ok())
+        .unwrap_or(DEFAULT_ENDPOINT.into())
+        .replace("{day}", &day.to_string());
+    let body = ureq::post(&endpoint)
+        .set("Cookie", &format!("session={}", session()?))
+        .send_form(&[("level", &part.to_string()), ("answer", answer)])
+        .map_err(|e| format!("Submitting to {} failed: {}", endpoint, e))?
+        .into_string()
+        .map_err(|e| format!("Could not read response: {}", e))?;
+
+    let outcome = Outcome::from_response(&body);
+    record(&Attempt {
+        time: now(),
+        day,
+        part,
+        answer: answer.into(),
+        outcome,
+    })?;
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(time: u64, part: u32, answer: &str, outcome: Outcome) -> Attempt {
+        Attempt {
+            time,
+            day: 1,
+            part,
+            answer: answer.into(),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn responses() {
+        let cases = [
+            (
+                "That's the right answer! You are one gold star closer",
+                Outcome::Correct,
+            ),
+            (
+                "That's not the right answer; your answer is too high.",
+                Outcome::TooHigh,
+            ),
+            (
+                "That's not the right answer; your answer is too low.",
+                Outcome::TooLow,
+            ),
+            (
+                "That's not the right answer. If you're stuck",
+                Outcome::Wrong,
+            ),
+            (
+                "You gave an answer too recently. You have 1m 23s left to wait.",
+                Outcome::Wait(83),
+            ),
+            ("You gave an answer too recently.", Outcome::Wait(COOLDOWN)),
+            (
+                "You don't seem to be solving the right level.",
+                Outcome::AlreadySolved,
+            ),
+            ("<html>Something else</html>", Outcome::Unknown),
+        ];
+        for (body, outcome) in cases {
+            assert_eq!(Outcome::from_response(body), outcome, "{}", body);
+        }
+    }
+
+    #[test]
+    fn outcomes_round_trip() {
+        for o in [
+            Outcome::Correct,
+            Outcome::Wrong,
+            Outcome::TooHigh,
+            Outcome::TooLow,
+            Outcome::Wait(42),
+            Outcome::AlreadySolved,
+            Outcome::Unknown,
+        ] {
+            assert_eq!(o.to_string().parse(), Ok(o));
+        }
+        assert!("wait:soon".parse::<Outcome>().is_err());
+    }
+
+    #[test]
+    fn waits() {
+        assert_eq!(parse_wait("You have 45s left to wait."), Some(45));
+        assert_eq!(parse_wait("You have 2m 5s left to wait."), Some(125));
+        assert_eq!(parse_wait("You have 3m left to wait."), Some(180));
+        assert_eq!(parse_wait("You have a while left to wait."), None);
+        assert_eq!(parse_wait("Please wait."), None);
+    }
+
+    #[test]
+    fn attempts_round_trip() {
+        let a = attempt(1700000000, 2, "1234", Outcome::TooLow);
+        let parsed: Attempt = a.to_string().parse().unwrap();
+        assert_eq!(parsed.to_string(), a.to_string());
+        assert!("1\t2\t3".parse::<Attempt>().is_err());
+    }
+
+    #[test]
+    fn throttle() {
+        let history = [attempt(1000, 1, "5", Outcome::Wrong)];
+        assert!(check(&history, 1, 1, "6", 1000 + COOLDOWN - 1).is_err());
+        assert!(check(&history, 1, 1, "6", 1000 + COOLDOWN).is_ok());
+
+        let history = [attempt(1000, 1, "5", Outcome::Wait(300))];
+        assert!(check(&history, 1, 1, "6", 1299).is_err());
+        assert!(check(&history, 1, 1, "6", 1300).is_ok());
+
+        // Only the last attempt throttles, and an unknown response doesn't
+        let history = [attempt(1000, 1, "5", Outcome::Unknown)];
+        assert!(check(&history, 1, 1, "6", 1001).is_ok());
+    }
+
+    #[test]
+    fn known_answers() {
+        let history = [
+            attempt(0, 1, "5", Outcome::Wrong),
+            attempt(0, 2, "7", Outcome::Correct),
+        ];
+        assert!(check(&history, 1, 1, "5", 1000).is_err());
+        assert!(check(&history, 1, 1, "6", 1000).is_ok());
+        assert!(check(&history, 1, 2, "8", 1000).is_err());
+        assert!(check(&history, 2, 1, "5", 1000).is_ok());
+    }
+
+    #[test]
+    fn bounds() {
+        let history = [
+            attempt(0, 1, "100", Outcome::TooHigh),
+            attempt(0, 1, "10", Outcome::TooLow),
+        ];
+        for (answer, ok) in [
+            ("100", false),
+            ("101", false),
+            ("99", true),
+            ("11", true),
+            ("10", false),
+            ("9", false),
+        ] {
+            assert_eq!(
+                check(&history, 1, 1, answer, 1000).is_ok(),
+                ok,
+                "{}",
+                answer
+            );
+        }
+        // Bounds don't apply to answers that aren't numbers, nor to the other part
+        assert!(check(&history, 1, 1, "abc", 1000).is_ok());
+        assert!(check(&history, 1, 2, "1000", 1000).is_ok());
+    }
+}
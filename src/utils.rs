@@ -1,4 +1,5 @@
 use std::io::BufRead;
+use std::ops::Range;
 use std::{fmt, fs, io};
 
 #[allow(dead_code)]
@@ -9,8 +10,22 @@ where
     Ok(io::BufReader::new(fs::File::open(filename)?).lines())
 }
 
+pub fn json_string(s: &str) -> String {
+    let mut json = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
 // Map type from https://fasterthanli.me/series/advent-of-code-2020/part-11
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Vec2 {
     pub x: i64,
     pub y: i64,
@@ -59,8 +74,7 @@ impl<T> Map<T> {
     #[allow(dead_code)]
     fn neighbor_positions(&self, pos: Vec2) -> impl Iterator<Item = Vec2> {
         (-1..=1)
-            .map(|dx| (-1..=1).map(move |dy| (dx, dy)))
-            .flatten()
+            .flat_map(|dx| (-1..=1).map(move |dy| (dx, dy)))
             .filter(|&(dx, dy)| !(dx == 0 && dy == 0))
             .map(move |(dx, dy)| Vec2 {
                 x: pos.x + dx,
@@ -87,14 +101,12 @@ where
 {
     #[allow(dead_code)]
     pub fn iter(&self) -> impl Iterator<Item = Positioned<T>> + '_ {
-        (0..self.size.y)
-            .map(move |y| {
-                (0..self.size.x).map(move |x| {
-                    let pos = Vec2 { x, y };
-                    Positioned(pos, self.get(pos).unwrap())
-                })
+        (0..self.size.y).flat_map(move |y| {
+            (0..self.size.x).map(move |x| {
+                let pos = Vec2 { x, y };
+                Positioned(pos, self.get(pos).unwrap())
             })
-            .flatten()
+        })
     }
     #[allow(dead_code)]
     pub fn neighbor_elements(&self, pos: Vec2) -> impl Iterator<Item = T> + '_ {
@@ -102,3 +114,420 @@ where
             .filter_map(move |pos| self.get(pos))
     }
 }
+
+/// Xorshift pseudo random numbers for checks and benchmarks, always starting from the same seed so
+/// runs can be repeated
+pub struct XorShift(u64);
+
+impl Default for XorShift {
+    fn default() -> Self {
+        XorShift(0x2545f4914f6cdd1d)
+    }
+}
+
+impl XorShift {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    /// A value in the non-empty `range`
+    pub fn within(&mut self, range: &Range<u64>) -> u64 {
+        range.start + self.next() % (range.end - range.start)
+    }
+}
+
+/// Piecewise translation of `u64`s, each source range shifts onto the range starting at its
+/// destination. Values outside every source range map to themselves
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IntervalMap {
+    // Sorted by start, non-empty and non-overlapping
+    ranges: Vec<(Range<u64>, u64)>,
+}
+
+impl IntervalMap {
+    /// Fails with the already present range when `source` overlaps it
+    pub fn insert(&mut self, source: Range<u64>, dest: u64) -> Result<(), Range<u64>> {
+        if source.is_empty() {
+            return Ok(());
+        }
+        let i = self.ranges.partition_point(|(r, _)| r.start < source.start);
+        if let Some((prev, _)) = i.checked_sub(1).map(|i| &self.ranges[i]) {
+            if prev.end > source.start {
+                return Err(prev.clone());
+            }
+        }
+        if let Some((next, _)) = self.ranges.get(i) {
+            if next.start < source.end {
+                return Err(next.clone());
+            }
+        }
+        self.ranges.insert(i, (source, dest));
+        Ok(())
+    }
+
+    pub fn ranges(&self) -> &[(Range<u64>, u64)] {
+        &self.ranges
+    }
+
+    pub fn get(&self, i: u64) -> u64 {
+        let index = self.ranges.partition_point(|(r, _)| r.end <= i);
+        match self.ranges.get(index) {
+            Some((range, dest)) if range.contains(&i) => dest + (i - range.start),
+            _ => i,
+        }
+    }
+
+    /// Splits `range` at every source range boundary into `(source piece, destination start)`
+    /// pairs, gaps between source ranges map onto themselves
+    pub fn pieces(&self, range: Range<u64>) -> Vec<(Range<u64>, u64)> {
+        let mut pieces = vec![];
+        let mut start = range.start;
+        let first = self.ranges.partition_point(|(r, _)| r.end <= range.start);
+        for (source, dest) in self.ranges[first..].iter() {
+            if source.start >= range.end || start >= range.end {
+                break;
+            }
+            if start < source.start {
+                pieces.push((start..source.start, start));
+                start = source.start;
+            }
+            let end = range.end.min(source.end);
+            pieces.push((start..end, dest + (start - source.start)));
+            start = end;
+        }
+        if start < range.end {
+            pieces.push((start..range.end, start));
+        }
+        pieces
+    }
+
+    /// Splits `range` at every source range boundary and maps the pieces, gaps between source
+    /// ranges come out unchanged
+    pub fn map_range(&self, range: Range<u64>) -> Vec<Range<u64>> {
+        self.pieces(range)
+            .into_iter()
+            .map(|(source, dest)| dest..dest + (source.end - source.start))
+            .collect()
+    }
+
+    pub fn map_ranges(&self, ranges: &[Range<u64>]) -> Vec<Range<u64>> {
+        merge_ranges(
+            ranges
+                .iter()
+                .flat_map(|r| self.map_range(r.clone()))
+                .collect(),
+        )
+    }
+
+    /// Every value mapping to `i`, several when a shifted range lands on values that are also
+    /// left in place or on another range's destination
+    pub fn preimage(&self, i: u64) -> Vec<u64> {
+        let mut sources: Vec<u64> = self
+            .ranges
+            .iter()
+            .filter(|(source, dest)| (*dest..dest + (source.end - source.start)).contains(&i))
+            .map(|(source, dest)| source.start + (i - dest))
+            .collect();
+        if self.get(i) == i {
+            sources.push(i);
+        }
+        sources.sort_unstable();
+        sources.dedup();
+        sources
+    }
+
+    /// Every value mapping into `range`
+    pub fn preimage_range(&self, range: Range<u64>) -> Vec<Range<u64>> {
+        let sources: Vec<Range<u64>> = self.ranges.iter().map(|(s, _)| s.clone()).collect();
+        let mut preimage = subtract_ranges(std::slice::from_ref(&range), &sources);
+        for (source, dest) in self.ranges.iter() {
+            let image = *dest..dest + (source.end - source.start);
+            let hit = range.start.max(image.start)..range.end.min(image.end);
+            if !hit.is_empty() {
+                preimage.push(source.start + (hit.start - dest)..source.start + (hit.end - dest));
+            }
+        }
+        merge_ranges(preimage)
+    }
+
+    /// The map undoing `self`, if every value has exactly one preimage
+    pub fn inverse(&self) -> Option<IntervalMap> {
+        let mut inverse = IntervalMap::default();
+        for (source, dest) in self.ranges.iter() {
+            let image = *dest..dest + (source.end - source.start);
+            inverse.insert(image, source.start).ok()?;
+        }
+        let sources: Vec<Range<u64>> = self.ranges.iter().map(|(s, _)| s.clone()).collect();
+        let images: Vec<Range<u64>> = inverse.ranges.iter().map(|(s, _)| s.clone()).collect();
+        match merge_ranges(sources) == merge_ranges(images) {
+            true => Some(inverse),
+            false => None,
+        }
+    }
+
+    /// The map applying `self` and then `next`. Pieces mapping onto themselves are left out and
+    /// neighbouring pieces with the same shift are joined, so equal functions compare equal
+    pub fn then(&self, next: &IntervalMap) -> IntervalMap {
+        let mut ranges: Vec<(Range<u64>, u64)> = vec![];
+        for (source, dest) in self.pieces(0..u64::MAX) {
+            for (image, to) in next.pieces(dest..dest + (source.end - source.start)) {
+                let start = source.start + (image.start - dest);
+                let piece = start..start + (image.end - image.start);
+                match ranges.last_mut() {
+                    _ if to == piece.start => {}
+                    Some((last, last_to))
+                        if last.end == piece.start && *last_to + (last.end - last.start) == to =>
+                    {
+                        last.end = piece.end
+                    }
+                    _ => ranges.push((piece, to)),
+                }
+            }
+        }
+        IntervalMap { ranges }
+    }
+
+    /// The lowest value any of `ranges` maps to, each piece is increasing so only the start of
+    /// every piece needs to be looked at
+    pub fn min_over(&self, ranges: &[Range<u64>]) -> Option<u64> {
+        ranges
+            .iter()
+            .flat_map(|r| self.pieces(r.clone()))
+            .map(|(_, dest)| dest)
+            .min()
+    }
+}
+
+impl fmt::Display for IntervalMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (source, dest) in self.ranges.iter() {
+            writeln!(
+                f,
+                "{}..{} -> {}..{} ({:+})",
+                source.start,
+                source.end,
+                dest,
+                dest + (source.end - source.start),
+                *dest as i128 - source.start as i128
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Sorted, with overlapping and adjacent ranges joined and empty ones dropped
+pub fn merge_ranges(mut ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    ranges.retain(|r| !r.is_empty());
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<u64>> = vec![];
+    for r in ranges {
+        match merged.last_mut() {
+            Some(last) if r.start <= last.end => last.end = last.end.max(r.end),
+            _ => merged.push(r),
+        }
+    }
+    merged
+}
+
+pub fn intersect_ranges(a: &[Range<u64>], b: &[Range<u64>]) -> Vec<Range<u64>> {
+    let (a, b) = (merge_ranges(a.to_vec()), merge_ranges(b.to_vec()));
+    let (mut i, mut j) = (0, 0);
+    let mut intersection = vec![];
+    while i < a.len() && j < b.len() {
+        let r = a[i].start.max(b[j].start)..a[i].end.min(b[j].end);
+        if !r.is_empty() {
+            intersection.push(r);
+        }
+        match a[i].end < b[j].end {
+            true => i += 1,
+            false => j += 1,
+        }
+    }
+    intersection
+}
+
+/// The parts of `a` not in `b`
+pub fn subtract_ranges(a: &[Range<u64>], b: &[Range<u64>]) -> Vec<Range<u64>> {
+    let b = merge_ranges(b.to_vec());
+    let mut difference = vec![];
+    for r in merge_ranges(a.to_vec()) {
+        let mut start = r.start;
+        for cut in b.iter().filter(|c| c.end > r.start && c.start < r.end) {
+            if start < cut.start {
+                difference.push(start..cut.start);
+            }
+            start = start.max(cut.end);
+        }
+        if start < r.end {
+            difference.push(start..r.end);
+        }
+    }
+    difference
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn values(ranges: &[Range<u64>]) -> BTreeSet<u64> {
+        ranges.iter().flat_map(|r| r.clone()).collect()
+    }
+
+    /// Every set of values below 8, as unsorted and partly overlapping ranges
+    fn range_sets() -> impl Iterator<Item = Vec<Range<u64>>> {
+        (0_u32..256).map(|mask| {
+            let mut ranges: Vec<Range<u64>> = (0..8)
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| i..i + 1)
+                .collect();
+            if let Some(last) = ranges.last() {
+                ranges.push(last.start.saturating_sub(2)..last.end);
+            }
+            ranges.reverse();
+            ranges.push(3..3);
+            ranges
+        })
+    }
+
+    fn is_normal(ranges: &[Range<u64>]) -> bool {
+        ranges.iter().all(|r| !r.is_empty()) && ranges.windows(2).all(|w| w[0].end < w[1].start)
+    }
+
+    #[test]
+    fn range_set_operations() {
+        for a in range_sets() {
+            let merged = merge_ranges(a.clone());
+            assert!(is_normal(&merged), "{:?}", merged);
+            assert_eq!(values(&merged), values(&a));
+            for b in range_sets() {
+                let (va, vb) = (values(&a), values(&b));
+                let intersection = intersect_ranges(&a, &b);
+                assert!(is_normal(&intersection), "{:?} & {:?}", a, b);
+                assert_eq!(values(&intersection), &va & &vb, "{:?} & {:?}", a, b);
+                let difference = subtract_ranges(&a, &b);
+                assert!(is_normal(&difference), "{:?} - {:?}", a, b);
+                assert_eq!(values(&difference), &va - &vb, "{:?} - {:?}", a, b);
+            }
+        }
+    }
+
+    /// Every map with `count` source ranges below 8 of length 1 to 3, shifted onto values below 8
+    fn maps(count: usize) -> Vec<IntervalMap> {
+        let mut maps = vec![IntervalMap::default()];
+        for _ in 0..count {
+            let mut more = vec![];
+            for map in maps.iter() {
+                let after = map.ranges().last().map_or(0, |(source, _)| source.end);
+                for (start, len, dest) in itertools::iproduct!(after..8, 1..4, 0..8) {
+                    let mut map = map.clone();
+                    map.insert(start..start + len, dest).unwrap();
+                    more.push(map);
+                }
+            }
+            maps = more;
+        }
+        maps
+    }
+
+    /// Looks `i` up by walking every range
+    fn get(map: &IntervalMap, i: u64) -> u64 {
+        map.ranges()
+            .iter()
+            .find(|(source, _)| source.contains(&i))
+            .map_or(i, |(source, dest)| dest + (i - source.start))
+    }
+
+    // Every source and destination ends below 11, so values from 16 on map to themselves
+    const VALUES: Range<u64> = 0..16;
+
+    #[test]
+    fn inserts() {
+        let mut map = IntervalMap::default();
+        assert_eq!(map.insert(5..10, 0), Ok(()));
+        assert_eq!(map.insert(0..5, 20), Ok(()));
+        assert_eq!(map.insert(10..10, 0), Ok(()));
+        assert_eq!(map.insert(9..12, 0), Err(5..10));
+        assert_eq!(map.insert(2..3, 0), Err(0..5));
+        assert_eq!(map.ranges(), [(0..5, 20), (5..10, 0)]);
+    }
+
+    #[test]
+    fn lookups() {
+        for map in maps(2) {
+            for i in VALUES {
+                assert_eq!(map.get(i), get(&map, i), "{} in\n{}", i, map);
+                let preimage: Vec<u64> = VALUES.filter(|&j| get(&map, j) == i).collect();
+                assert_eq!(map.preimage(i), preimage, "{} in\n{}", i, map);
+            }
+            for (start, len) in itertools::iproduct!(0..12, 0..5) {
+                let range = start..start + len;
+                let preimage: BTreeSet<u64> =
+                    VALUES.filter(|&j| range.contains(&get(&map, j))).collect();
+                assert_eq!(
+                    values(&map.preimage_range(range.clone())),
+                    preimage,
+                    "{:?} in\n{}",
+                    range,
+                    map
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn inverses() {
+        for map in maps(2) {
+            let image: BTreeSet<u64> = VALUES.map(|i| get(&map, i)).collect();
+            let bijective = image == VALUES.collect();
+            match map.inverse() {
+                Some(inverse) => {
+                    assert!(bijective, "\n{}", map);
+                    for i in VALUES {
+                        assert_eq!(inverse.get(map.get(i)), i, "{} in\n{}", i, map);
+                    }
+                }
+                None => assert!(!bijective, "\n{}", map),
+            }
+        }
+    }
+
+    #[test]
+    fn compositions() {
+        let maps = maps(1);
+        for (a, b) in itertools::iproduct!(maps.iter(), maps.iter()) {
+            let composed = a.then(b);
+            for i in VALUES {
+                assert_eq!(
+                    composed.get(i),
+                    get(b, get(a, i)),
+                    "{} in\n{}then\n{}",
+                    i,
+                    a,
+                    b
+                );
+            }
+            // Nothing but the shifted values is kept
+            let moved: Vec<u64> = VALUES.filter(|&i| composed.get(i) != i).collect();
+            assert!(composed
+                .ranges()
+                .iter()
+                .all(|(source, _)| source.clone().all(|i| moved.contains(&i))));
+        }
+        let map = |shifts: &[(Range<u64>, u64)]| {
+            let mut map = IntervalMap::default();
+            for (source, dest) in shifts {
+                map.insert(source.clone(), *dest).unwrap();
+            }
+            map
+        };
+        let swap = map(&[(0..4, 4), (4..8, 0)]);
+        assert_eq!(swap.then(&swap), IntervalMap::default());
+        assert_eq!(
+            map(&[(0..2, 10), (2..4, 12)]).then(&map(&[(10..14, 20)])),
+            map(&[(0..4, 20), (10..14, 20)])
+        );
+    }
+}
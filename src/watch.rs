@@ -0,0 +1,89 @@
+use colored::Colorize;
+use std::{
+    env, fs,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+const POLL: Duration = Duration::from_millis(500);
+
+fn modified(paths: &[String]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+fn cargo(command: &str) -> Command {
+    let mut cargo = Command::new("cargo");
+    cargo.args([command, "--quiet"]);
+    if !cfg!(debug_assertions) {
+        cargo.arg("--release");
+    }
+    cargo
+}
+
+fn run_tests(day: u32) -> bool {
+    cargo("test")
+        .arg(format!("day{}::", day))
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+// The solver runs in a fresh build so edits to the day module are picked up, with the same options
+fn solve() -> Option<[String; 2]> {
+    let output = cargo("run")
+        .arg("--")
+        .args(env::args().skip(1).filter(|a| a != "--watch"))
+        .stderr(Stdio::inherit())
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (ans1, ans2) = stdout
+        .lines()
+        .find_map(|l| l.strip_prefix("part1: "))?
+        .split_once(", part2: ")?;
+    Some([ans1.into(), ans2.into()])
+}
+
+fn report(prev: &Option<[String; 2]>, answers: &[String; 2]) {
+    for (i, ans) in answers.iter().enumerate() {
+        match prev.as_ref().map(|p| &p[i]) {
+            Some(old) if old != ans => {
+                println!("part{}: {} -> {}", i + 1, old.yellow(), ans.green())
+            }
+            _ => println!("part{}: {}", i + 1, ans),
+        }
+    }
+}
+
+pub fn watch(day: u32) -> ! {
+    let paths = [
+        format!("src/day{}.rs", day),
+        crate::runner::input_path(day),
+        crate::runner::example_path(day),
+    ];
+    println!("Watching {}", paths.join(", "));
+    let mut seen = vec![];
+    let mut prev = None;
+    loop {
+        let stamps = modified(&paths);
+        if stamps != seen {
+            seen = stamps;
+            match run_tests(day) {
+                true => println!("{}", "tests passed".green()),
+                false => println!("{}", "tests failed".red()),
+            }
+            match solve() {
+                Some(answers) => {
+                    report(&prev, &answers);
+                    prev = Some(answers);
+                }
+                None => println!("{}", "solver failed".red()),
+            }
+        }
+        thread::sleep(POLL);
+    }
+}